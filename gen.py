@@ -7,102 +7,301 @@
 # option. This file may not be copied, modified, or distributed
 # except according to those terms.
 
-import argparse, subprocess;
-
-bindgen = "bindgen"
-
-azure = "azure-c.h"
-includes = [
-    "-I", "include"
-    ]
-sysincludes = [
-    "-isystem", "/usr/lib/x86_64-linux-gnu/gcc/x86_64-linux-gnu/4.5/include",
-    "-isystem", "/usr/lib/x86_64-linux-gnu/gcc/x86_64-linux-gnu/4.5/include-fixed",
-    "-isystem", "/usr/lib/gcc/x86_64-linux-gnu/4.6/include",
-    "-isystem", "/usr/include/cairo",
-    "-isystem", "/usr/include/freetype2",
-    ]
-otherflags = [
-    "-DMOZ_GFX",
-    "-DUSE_CAIRO",
-    "-DNS_ATTR_MALLOC=",
-    "-DNS_WARN_UNUSED_RESULT="
-    ]
-
-args = [
-    bindgen,
-    "-l", "azure",
-    "-o", "azure.rs",
-    "-match", "azure-c",
-    azure]
-args += includes + sysincludes + otherflags
-
-subprocess.call(args)
-
-cairo = "/usr/include/cairo/cairo.h"
-args = [
-    bindgen,
-    "-l", "cairo",
-    "-o", "cairo.rs",
-    "-match", "cairo",
-    cairo]
-args += includes + sysincludes
-
-subprocess.call(args)
-
-cairo_ft = "/usr/include/cairo/cairo-ft.h"
-args = [
-    bindgen,
-    "-l", "cairo",
-    "-o", "cairo_ft.rs",
-    "-match", "cairo-ft",
-    cairo_ft]
-args += includes + sysincludes + ["-DCAIRO_HAS_FT_FONT", "-DCAIRO_HAS_FC_FONT"]
-
-subprocess.call(args)
-
-cairo_xlib = "/usr/include/cairo/cairo-xlib.h"
-args = [
-    bindgen,
-    "-l", "cairo",
-    "-o", "cairo_xlib.rs",
-    "-match", "cairo-xlib",
-    cairo_xlib]
-args += includes + sysincludes
-
-subprocess.call(args)
-
-ft = "/usr/include/freetype2/freetype/freetype.h"
-args = [
-    bindgen,
-    "-l", "freetype",
-    "-o", "freetype.rs",
-    "-match", "freetype",
-    cairo_ft]
-args += includes + sysincludes
-
-subprocess.call(args)
-
-fc = "/usr/include/fontconfig/fontconfig.h"
-args = [
-    bindgen,
-    "-l", "fontconfig",
-    "-o", "fontconfig.rs",
-    "-match", "fontconfig",
-    cairo_ft]
-args += includes + sysincludes
-
-subprocess.call(args)
-
-xlib = "/usr/include/X11/Xlib.h"
-args = [
-    bindgen,
-    "-l", "X11",
-    "-o", "xlib.rs",
-    "-match", "Xlib",
-    "-match", "X",
-    xlib]
-args += includes + sysincludes
-
-subprocess.call(args)
-        
+# Generates the Rust FFI module (src/azure.rs) from the C API in src/azure-c.h.
+#
+# azure-c.h is restricted to a small subset of C: enums, `typedef struct`s, typedefs of
+# opaque pointers, scalar types and function pointers, and function prototypes. This script
+# understands exactly that subset, so it needs neither clang nor bindgen, and fails loudly on
+# anything else.
+#
+# Usage: python gen.py src/azure-c.h src/azure.rs
+
+import re
+import sys
+
+HEADER = """\
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/* automatically generated by gen.py from azure-c.h; do not edit */
+
+#![allow(non_upper_case_globals)]
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+
+use libc::*;
+"""
+
+# C scalar types and the libc names they map to.
+SCALAR_TYPES = {
+    "void": "c_void",
+    "bool": "bool",
+    "char": "c_char",
+    "unsigned char": "c_uchar",
+    "int": "c_int",
+    "unsigned int": "c_uint",
+    "float": "c_float",
+    "double": "c_double",
+    "size_t": "size_t",
+    "int8_t": "int8_t",
+    "int16_t": "int16_t",
+    "int32_t": "int32_t",
+    "int64_t": "int64_t",
+    "uint8_t": "uint8_t",
+    "uint16_t": "uint16_t",
+    "uint32_t": "uint32_t",
+    "uint64_t": "uint64_t",
+}
+
+
+class GenError(Exception):
+    pass
+
+
+def preprocess(text):
+    """Strips comments and evaluates the `#ifdef`/`#ifndef` blocks in the header. Nothing is
+    considered defined, so C++-only blocks are dropped and include guards are kept."""
+    text = re.sub(r"/\*.*?\*/", " ", text, flags=re.S)
+    text = re.sub(r"//[^\n]*", " ", text)
+
+    output = []
+    stack = []
+    for line in text.split("\n"):
+        stripped = line.strip()
+        if stripped.startswith("#"):
+            directive = stripped[1:].split()
+            if directive[0] == "ifdef":
+                stack.append(False)
+            elif directive[0] == "ifndef":
+                stack.append(True)
+            elif directive[0] == "else":
+                stack[-1] = not stack[-1]
+            elif directive[0] == "endif":
+                stack.pop()
+            elif directive[0] not in ("include", "define"):
+                raise GenError("unsupported preprocessor directive: " + stripped)
+            continue
+        if all(stack):
+            output.append(line)
+    return "\n".join(output)
+
+
+def statements(text):
+    """Splits the header into top-level statements, keeping braced bodies intact."""
+    result = []
+    depth = 0
+    current = []
+    for char in text:
+        if char == "{":
+            depth += 1
+        elif char == "}":
+            depth -= 1
+        if char == ";" and depth == 0:
+            statement = " ".join("".join(current).split())
+            if statement:
+                result.append(statement)
+            current = []
+        else:
+            current.append(char)
+    if "".join(current).strip():
+        raise GenError("trailing input without a semicolon: " + "".join(current).strip())
+    return result
+
+
+class Generator:
+    def __init__(self):
+        self.enums = set()
+        self.types = set()
+        self.opaque_types = set()
+        self.items = []
+        self.functions = []
+
+    def rust_type(self, c_type, is_return=False):
+        """Converts a C type such as `const AzPoint *` into its Rust equivalent."""
+        c_type = c_type.replace("*", " * ")
+        tokens = c_type.split()
+        pointers = tokens.count("*")
+        tokens = [token for token in tokens if token != "*"]
+
+        is_const = "const" in tokens
+        tokens = [token for token in tokens if token != "const"]
+
+        if tokens[0] == "enum":
+            base = "enum_" + tokens[1]
+        elif tokens[0] == "struct":
+            base = "struct_" + tokens[1]
+        else:
+            name = " ".join(tokens)
+            if name in SCALAR_TYPES:
+                base = SCALAR_TYPES[name]
+            elif name in self.types or name in self.enums:
+                base = name
+            elif name in self.opaque_types:
+                base = name
+            elif pointers > 0:
+                # A pointer to a C++ type that isn't exposed to Rust.
+                base = "c_void"
+            else:
+                raise GenError("unknown type: " + name)
+
+        if base == "c_void" and pointers == 0:
+            if is_return:
+                return None
+            raise GenError("void used as a value type")
+
+        rust = base
+        for level in range(pointers):
+            if level == 0 and is_const:
+                rust = "*const " + rust
+            else:
+                rust = "*mut " + rust
+        return rust
+
+    def split_declaration(self, declaration):
+        """Splits `const AzPoint *aPoint` into (`const AzPoint *`, `aPoint`)."""
+        match = re.match(r"^(.*?)(\w+)$", declaration.strip())
+        if not match:
+            raise GenError("can't parse declaration: " + declaration)
+        return match.group(1).strip(), match.group(2)
+
+    def add_enum(self, name, body):
+        self.enums.add(name)
+        lines = ["pub type enum_%s = c_uint;" % name]
+        value = 0
+        for variant in body.split(","):
+            variant = variant.strip()
+            if not variant:
+                continue
+            if "=" in variant:
+                variant, explicit = [part.strip() for part in variant.split("=")]
+                value = int(explicit, 0)
+            lines.append("pub static %s: u32 = %d_u32;" % (variant, value))
+            value += 1
+        lines.append("")
+        lines.append("pub type %s = enum_%s;" % (name, name))
+        self.items.append("\n".join(lines))
+
+    def add_struct(self, tag, body, name):
+        fields = []
+        for declaration in body.split(";"):
+            declaration = declaration.strip()
+            if not declaration:
+                continue
+            declarators = [part.strip() for part in declaration.split(",")]
+            base, first = self.split_declaration(declarators[0])
+            names = [first]
+            for declarator in declarators[1:]:
+                if declarator.startswith("*"):
+                    raise GenError("mixed pointer declarators in " + declaration)
+                names.append(declarator)
+            for field in names:
+                fields.append("    pub %s: %s," % (field, self.rust_type(base)))
+
+        self.types.add(name)
+        self.types.add("struct_" + tag)
+        self.items.append("\n".join([
+            "#[repr(C)]",
+            "#[deriving(Clone)]",
+            "pub struct struct_%s {" % tag,
+        ] + fields + [
+            "}",
+            "",
+            "pub type %s = struct_%s;" % (name, tag),
+        ]))
+
+    def add_typedef(self, declaration):
+        original, name = self.split_declaration(declaration)
+        tokens = original.replace("*", " * ").split()
+        if tokens == ["void"]:
+            self.opaque_types.add(name)
+            self.items.append("pub type %s = c_void;" % name)
+            return
+        base = " ".join(token for token in tokens if token not in ("*", "const"))
+        if base in SCALAR_TYPES or base in self.types or base in self.enums:
+            rust = self.rust_type(original)
+        else:
+            # Typedefs of C++ types, and pointers to them, are opaque to Rust.
+            rust = "*mut c_void"
+        self.types.add(name)
+        self.items.append("pub type %s = %s;" % (name, rust))
+
+    def signature(self, return_type, params):
+        """Converts a C return type and parameter list into Rust's `(params) -> return`."""
+        rust_params = []
+        for param in params.split(","):
+            param = param.strip()
+            if not param or param == "void":
+                continue
+            param_type, param_name = self.split_declaration(param)
+            rust_params.append("%s: %s" % (param_name, self.rust_type(param_type)))
+
+        rust_return = self.rust_type(return_type, is_return=True)
+        signature = "(%s)" % ", ".join(rust_params)
+        if rust_return is not None:
+            signature += " -> " + rust_return
+        return signature
+
+    def add_function_pointer(self, return_type, name, params):
+        self.types.add(name)
+        self.items.append("pub type %s = extern \"C\" fn%s;" %
+                          (name, self.signature(return_type, params)))
+
+    def add_function(self, statement):
+        match = re.match(r"^(.*?)\(([^()]*)\)$", statement)
+        return_type, name = self.split_declaration(match.group(1))
+        self.functions.append("pub fn %s%s;" % (name, self.signature(return_type, match.group(2))))
+
+    def add_statement(self, statement):
+        enum = re.match(r"^enum (\w+) \{(.*)\}$", statement)
+        struct = re.match(r"^typedef struct (\w+) \{(.*)\} (\w+)$", statement)
+        function_pointer = re.match(r"^typedef (.*?)\(\*(\w+)\)\(([^()]*)\)$", statement)
+        if enum:
+            self.add_enum(enum.group(1), enum.group(2))
+        elif struct:
+            self.add_struct(struct.group(1), struct.group(2), struct.group(3))
+        elif function_pointer:
+            self.add_function_pointer(function_pointer.group(1).strip(), function_pointer.group(2),
+                                      function_pointer.group(3))
+        elif statement.startswith("typedef "):
+            self.add_typedef(statement[len("typedef "):])
+        elif statement.endswith(")"):
+            self.add_function(statement)
+        else:
+            raise GenError("unsupported declaration: " + statement)
+
+    def output(self):
+        return "\n".join([
+            HEADER,
+            "\n\n".join(self.items),
+            "",
+            "#[link(name = \"azure\")]",
+            "extern {",
+            "",
+            "\n\n".join(self.functions),
+            "",
+            "}",
+            "",
+        ])
+
+
+def main():
+    if len(sys.argv) != 3:
+        sys.stderr.write("usage: %s <azure-c.h> <azure.rs>\n" % sys.argv[0])
+        sys.exit(2)
+
+    generator = Generator()
+    try:
+        with open(sys.argv[1]) as header:
+            for statement in statements(preprocess(header.read())):
+                generator.add_statement(statement)
+    except GenError as error:
+        sys.stderr.write("%s: %s\n" % (sys.argv[1], error))
+        sys.exit(1)
+
+    with open(sys.argv[2], "w") as output:
+        output.write(generator.output())
+
+
+if __name__ == "__main__":
+    main()
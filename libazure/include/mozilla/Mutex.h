@@ -0,0 +1,124 @@
+/* -*- Mode: C++; tab-width: 2; indent-tabs-mode: nil; c-basic-offset: 2 -*- */
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+/* A mutex and RAII helpers for locking it, with the same interface as XPCOM's. */
+
+#ifndef mozilla_Mutex_h_
+#define mozilla_Mutex_h_
+
+#include "mozilla/Attributes.h"
+
+#ifdef WIN32
+#include <windows.h>
+#else
+#include <pthread.h>
+#endif
+
+namespace mozilla {
+
+/**
+ * A non-recursive mutex. The name is for debugging only.
+ */
+class Mutex
+{
+public:
+  explicit Mutex(const char* aName)
+  {
+#ifdef WIN32
+    InitializeCriticalSection(&mMutex);
+#else
+    pthread_mutex_init(&mMutex, nullptr);
+#endif
+  }
+
+  ~Mutex()
+  {
+#ifdef WIN32
+    DeleteCriticalSection(&mMutex);
+#else
+    pthread_mutex_destroy(&mMutex);
+#endif
+  }
+
+  void Lock()
+  {
+#ifdef WIN32
+    EnterCriticalSection(&mMutex);
+#else
+    pthread_mutex_lock(&mMutex);
+#endif
+  }
+
+  void Unlock()
+  {
+#ifdef WIN32
+    LeaveCriticalSection(&mMutex);
+#else
+    pthread_mutex_unlock(&mMutex);
+#endif
+  }
+
+private:
+  Mutex(const Mutex&) MOZ_DELETE;
+  Mutex& operator=(const Mutex&) MOZ_DELETE;
+
+#ifdef WIN32
+  CRITICAL_SECTION mMutex;
+#else
+  pthread_mutex_t mMutex;
+#endif
+};
+
+/**
+ * Holds aMutex locked for its lifetime.
+ */
+class MutexAutoLock
+{
+public:
+  explicit MutexAutoLock(Mutex& aMutex)
+    : mMutex(aMutex)
+  {
+    mMutex.Lock();
+  }
+
+  ~MutexAutoLock()
+  {
+    mMutex.Unlock();
+  }
+
+private:
+  MutexAutoLock(const MutexAutoLock&) MOZ_DELETE;
+  MutexAutoLock& operator=(const MutexAutoLock&) MOZ_DELETE;
+
+  Mutex& mMutex;
+};
+
+/**
+ * Unlocks aMutex, which must be locked, for its lifetime.
+ */
+class MutexAutoUnlock
+{
+public:
+  explicit MutexAutoUnlock(Mutex& aMutex)
+    : mMutex(aMutex)
+  {
+    mMutex.Unlock();
+  }
+
+  ~MutexAutoUnlock()
+  {
+    mMutex.Lock();
+  }
+
+private:
+  MutexAutoUnlock(const MutexAutoUnlock&) MOZ_DELETE;
+  MutexAutoUnlock& operator=(const MutexAutoUnlock&) MOZ_DELETE;
+
+  Mutex& mMutex;
+};
+
+} // namespace mozilla
+
+#endif /* mozilla_Mutex_h_ */
@@ -519,6 +519,10 @@ struct FontOptions
   FontStyle mStyle;
   uint8_t *mData;
   uint32_t mDataSize;
+  /* When set, the face is loaded from this file instead of mData or mName. */
+  std::string mFile;
+  /* The face to use within a TrueType collection in mData or mFile. */
+  uint32_t mFaceIndex;
 };
 #endif
 
@@ -566,6 +570,20 @@ public:
    */
   virtual void Flush() = 0;
 
+  /**
+   * Copies the contents of aRect, which must lie within the draw target, into
+   * aData as rows aStride bytes apart, in the draw target's format. Returns
+   * false if that isn't possible. The default implementation reads back a
+   * Snapshot(); backends override it to read only aRect where they can.
+   */
+  virtual bool ReadPixels(const IntRect &aRect, uint8_t *aData, int32_t aStride);
+
+  /**
+   * Returns a copy of the contents of aRect, which must lie within the draw
+   * target, made with ReadPixels.
+   */
+  TemporaryRef<DataSourceSurface> SnapshotRect(const IntRect &aRect);
+
   /*
    * Draw a surface to the draw target. Possibly doing partial drawing or
    * applying scaling. No sampling happens outside the source.
@@ -859,11 +877,27 @@ public:
   virtual ~DrawEventRecorder() { }
 };
 
+/* Called by a ScaledFont created from TrueType data once it no longer reads the data. */
+typedef void (*FontDataReleaseFunc)(void *aClosure);
+
 class GFX2D_API Factory
 {
 public:
   static bool HasSSE2();
 
+  /**
+   * Returns whether the processor supports SSE2, regardless of SetSSE2Disabled.
+   */
+  static bool CPUSupportsSSE2();
+
+  /**
+   * Makes HasSSE2 return false, so that code with both an SSE2 and a scalar
+   * version takes the scalar one. This is meant for testing both versions. It
+   * may be called from any thread, but drawing already under way on other
+   * threads may take either version.
+   */
+  static void SetSSE2Disabled(bool aDisabled);
+
   static TemporaryRef<DrawTarget> CreateDrawTargetForCairoSurface(cairo_surface_t* aSurface, const IntSize& aSize);
 
   static TemporaryRef<DrawTarget>
@@ -886,9 +920,13 @@ public:
    * aFaceIndex - Index of the font face in the truetype data this ScaledFont needs to represent.
    * aGlyphSize - Size of the glyphs in this ScaledFont
    * aType - Type of ScaledFont that should be created.
+   * aRelease - If set, the font may read aData in place rather than copying it, and calls
+   *            aRelease(aClosure) exactly once when it is done with it, which may be before
+   *            this returns. If null, the data is copied.
    */
   static TemporaryRef<ScaledFont>
-    CreateScaledFontForTrueTypeData(uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex, Float aGlyphSize, FontType aType);
+    CreateScaledFontForTrueTypeData(uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex, Float aGlyphSize, FontType aType,
+                                    FontDataReleaseFunc aRelease = nullptr, void *aClosure = nullptr);
 
   /*
    * This creates a scaled font with an associated cairo_scaled_font_t, and
@@ -948,6 +986,7 @@ private:
 #endif
 
   static DrawEventRecorder *mRecorder;
+  static bool mSSE2Disabled;
 };
 
 }
@@ -35,7 +35,13 @@ enum SurfaceFormat
   FORMAT_B8G8R8A8,
   FORMAT_B8G8R8X8,
   FORMAT_R5G6B5,
-  FORMAT_A8
+  FORMAT_A8,
+  // The formats below are only understood as source data. Backends convert
+  // them to the matching BGRA format; see ToBGRAFormat in Tools.h.
+  FORMAT_R8G8B8A8,
+  FORMAT_R8G8B8X8,
+  // Premultiplied, with a native-endian 16-bit integer per channel.
+  FORMAT_R16G16B16A16
 };
 
 enum BackendType
@@ -84,6 +90,14 @@ enum FontStyle
   FONT_STYLE_BOLD_ITALIC
 };
 
+enum FontHinting
+{
+  FONT_HINTING_NONE,
+  FONT_HINTING_LIGHT,
+  FONT_HINTING_NORMAL,
+  FONT_HINTING_FULL
+};
+
 enum CompositionOp { OP_OVER, OP_ADD, OP_ATOP, OP_OUT, OP_IN, OP_SOURCE, OP_DEST_IN, OP_DEST_OUT, OP_DEST_OVER, OP_DEST_ATOP, OP_XOR, 
   OP_MULTIPLY, OP_SCREEN, OP_OVERLAY, OP_DARKEN, OP_LIGHTEN, OP_COLOR_DODGE, OP_COLOR_BURN, OP_HARD_LIGHT, OP_SOFT_LIGHT,  OP_DIFFERENCE, OP_EXCLUSION, OP_HUE, OP_SATURATION, OP_COLOR, OP_LUMINOSITY, OP_COUNT };
 enum ExtendMode { EXTEND_CLAMP, EXTEND_REPEAT, EXTEND_REFLECT };
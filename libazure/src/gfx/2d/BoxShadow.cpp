@@ -0,0 +1,487 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "BoxShadow.h"
+
+#include "mozilla/gfx/Blur.h"
+#include "mozilla/Mutex.h"
+
+#include <math.h>
+#include <algorithm>
+#include <list>
+#include <vector>
+
+using namespace std;
+
+namespace mozilla {
+namespace gfx {
+
+/**
+ * A blurred shadow mask. The shape is a rounded rect of integer size, placed at
+ * (mBlurRadius, mBlurRadius) in a mask that is larger than it by the blur radius on every
+ * side. Outset masks are opaque inside the shape; inset masks are opaque outside it.
+ */
+struct MaskKey
+{
+  IntSize mShapeSize;
+  RectCornerRadii mRadii;
+  int32_t mBlurRadius;
+  bool mInset;
+
+  bool operator==(const MaskKey &aOther) const
+  {
+    return mShapeSize == aOther.mShapeSize &&
+           mRadii.mTopLeft == aOther.mRadii.mTopLeft &&
+           mRadii.mTopRight == aOther.mRadii.mTopRight &&
+           mRadii.mBottomRight == aOther.mRadii.mBottomRight &&
+           mRadii.mBottomLeft == aOther.mRadii.mBottomLeft &&
+           mBlurRadius == aOther.mBlurRadius &&
+           mInset == aOther.mInset;
+  }
+};
+
+struct BlurredMask
+{
+  MaskKey mKey;
+  IntSize mSize;
+  int32_t mStride;
+  std::vector<uint8_t> mData;
+};
+
+// Masks are at most a few times the size of the corners, so a small cache covers the
+// shadows of a typical page.
+static const size_t kMaxCachedMasks = 32;
+
+// The cache is shared by all draw targets, which may be painted on different threads. The
+// most recently used mask comes first.
+static Mutex sMaskCacheLock("BoxShadow mask cache");
+static std::list<BlurredMask> sMaskCache;
+
+// Each pixel touching a corner is sampled on a grid of this many points per side.
+static const int32_t kSubsamples = 4;
+
+static Size
+SpreadCornerRadius(const Size &aRadius, Float aSpread)
+{
+  // Square corners stay square.
+  if (aRadius.width <= 0 || aRadius.height <= 0) {
+    return Size(0, 0);
+  }
+  return Size(max(aRadius.width + aSpread, 0.0f), max(aRadius.height + aSpread, 0.0f));
+}
+
+/* Scales down all the radii if adjacent corners would overlap, as CSS does. */
+static void
+ScaleRadiiToFit(RectCornerRadii &aRadii, const Size &aSize)
+{
+  Float sums[4] = {
+    aRadii.mTopLeft.width + aRadii.mTopRight.width,
+    aRadii.mBottomLeft.width + aRadii.mBottomRight.width,
+    aRadii.mTopLeft.height + aRadii.mBottomLeft.height,
+    aRadii.mTopRight.height + aRadii.mBottomRight.height
+  };
+  Float lengths[4] = { aSize.width, aSize.width, aSize.height, aSize.height };
+
+  Float scale = 1.0f;
+  for (int i = 0; i < 4; i++) {
+    if (sums[i] > lengths[i]) {
+      scale = min(scale, lengths[i] / sums[i]);
+    }
+  }
+  if (scale < 1.0f) {
+    Size *radii[4] = { &aRadii.mTopLeft, &aRadii.mTopRight,
+                       &aRadii.mBottomRight, &aRadii.mBottomLeft };
+    for (int i = 0; i < 4; i++) {
+      radii[i]->width *= scale;
+      radii[i]->height *= scale;
+    }
+  }
+}
+
+static bool
+InsideRoundedRect(Float aX, Float aY, const Rect &aRect, const RectCornerRadii &aRadii)
+{
+  if (aX < aRect.x || aY < aRect.y || aX >= aRect.XMost() || aY >= aRect.YMost()) {
+    return false;
+  }
+
+  const Size *radius;
+  Point center;
+  if (aX < aRect.x + aRadii.mTopLeft.width && aY < aRect.y + aRadii.mTopLeft.height) {
+    radius = &aRadii.mTopLeft;
+    center = Point(aRect.x + radius->width, aRect.y + radius->height);
+  } else if (aX > aRect.XMost() - aRadii.mTopRight.width &&
+             aY < aRect.y + aRadii.mTopRight.height) {
+    radius = &aRadii.mTopRight;
+    center = Point(aRect.XMost() - radius->width, aRect.y + radius->height);
+  } else if (aX > aRect.XMost() - aRadii.mBottomRight.width &&
+             aY > aRect.YMost() - aRadii.mBottomRight.height) {
+    radius = &aRadii.mBottomRight;
+    center = Point(aRect.XMost() - radius->width, aRect.YMost() - radius->height);
+  } else if (aX < aRect.x + aRadii.mBottomLeft.width &&
+             aY > aRect.YMost() - aRadii.mBottomLeft.height) {
+    radius = &aRadii.mBottomLeft;
+    center = Point(aRect.x + radius->width, aRect.YMost() - radius->height);
+  } else {
+    return true;
+  }
+
+  Float dx = (aX - center.x) / radius->width;
+  Float dy = (aY - center.y) / radius->height;
+  return dx * dx + dy * dy <= 1.0f;
+}
+
+/* Returns how much of the pixel at (aX, aY) a rounded rect with integer bounds covers. */
+static uint8_t
+PixelCoverage(int32_t aX, int32_t aY, const Rect &aRect, const RectCornerRadii &aRadii)
+{
+  Rect pixel(aX, aY, 1, 1);
+  if (!pixel.Intersects(aRect)) {
+    return 0;
+  }
+
+  Rect corners[4] = {
+    Rect(aRect.x, aRect.y, aRadii.mTopLeft.width, aRadii.mTopLeft.height),
+    Rect(aRect.XMost() - aRadii.mTopRight.width, aRect.y,
+         aRadii.mTopRight.width, aRadii.mTopRight.height),
+    Rect(aRect.XMost() - aRadii.mBottomRight.width, aRect.YMost() - aRadii.mBottomRight.height,
+         aRadii.mBottomRight.width, aRadii.mBottomRight.height),
+    Rect(aRect.x, aRect.YMost() - aRadii.mBottomLeft.height,
+         aRadii.mBottomLeft.width, aRadii.mBottomLeft.height)
+  };
+  bool touchesCorner = false;
+  for (int i = 0; i < 4; i++) {
+    touchesCorner = touchesCorner || pixel.Intersects(corners[i]);
+  }
+  if (!touchesCorner) {
+    return 255;
+  }
+
+  int32_t inside = 0;
+  for (int32_t y = 0; y < kSubsamples; y++) {
+    for (int32_t x = 0; x < kSubsamples; x++) {
+      if (InsideRoundedRect(aX + (x + 0.5f) / kSubsamples, aY + (y + 0.5f) / kSubsamples,
+                            aRect, aRadii)) {
+        inside++;
+      }
+    }
+  }
+  return uint8_t(inside * 255 / (kSubsamples * kSubsamples));
+}
+
+static void
+CreateMask(BlurredMask &aMask)
+{
+  const MaskKey &key = aMask.mKey;
+  const RectCornerRadii &radii = key.mRadii;
+  Rect shape(0, 0, key.mShapeSize.width, key.mShapeSize.height);
+
+  // Away from the corners, the inside of the shape is uniform and needn't be blurred.
+  // AlphaBoxBlur shrinks the skip rect by the blur radius itself. Without any blur there
+  // is nothing to skip, and AlphaBoxBlur doesn't allocate a surface for a skip rect
+  // covering all of it.
+  Float left = max(radii.mTopLeft.width, radii.mBottomLeft.width);
+  Float right = max(radii.mTopRight.width, radii.mBottomRight.width);
+  Float top = max(radii.mTopLeft.height, radii.mTopRight.height);
+  Float bottom = max(radii.mBottomLeft.height, radii.mBottomRight.height);
+  Rect skipRect(left, top, shape.width - left - right, shape.height - top - bottom);
+
+  IntSize blurRadius(key.mBlurRadius, key.mBlurRadius);
+  AlphaBoxBlur blur(shape, IntSize(0, 0), blurRadius, nullptr,
+                    key.mBlurRadius > 0 ? &skipRect : nullptr);
+  uint8_t *data = blur.GetData();
+  IntRect rect = blur.GetRect();
+  int32_t stride = blur.GetStride();
+  if (!data) {
+    aMask.mSize = IntSize(0, 0);
+    aMask.mStride = 0;
+    aMask.mData.clear();
+    return;
+  }
+
+  shape.MoveBy(-rect.x, -rect.y);
+  for (int32_t y = 0; y < rect.height; y++) {
+    for (int32_t x = 0; x < rect.width; x++) {
+      uint8_t coverage = PixelCoverage(x, y, shape, radii);
+      data[y * stride + x] = key.mInset ? 255 - coverage : coverage;
+    }
+  }
+  blur.Blur();
+
+  aMask.mSize = rect.Size();
+  aMask.mStride = stride;
+  aMask.mData.assign(data, data + stride * rect.height);
+}
+
+static TemporaryRef<SourceSurface>
+CreateShadowSurface(DrawTarget *aDT, const BlurredMask &aMask, const Color &aColor)
+{
+  if (aMask.mData.empty()) {
+    return nullptr;
+  }
+
+  int32_t stride = aMask.mSize.width * 4;
+  std::vector<uint8_t> data(stride * aMask.mSize.height);
+  // Premultiplied, in B8G8R8A8 byte order.
+  Float color[4] = { aColor.b * aColor.a, aColor.g * aColor.a, aColor.r * aColor.a, aColor.a };
+  for (int32_t y = 0; y < aMask.mSize.height; y++) {
+    const uint8_t *mask = &aMask.mData[y * aMask.mStride];
+    uint8_t *pixel = &data[y * stride];
+    for (int32_t x = 0; x < aMask.mSize.width; x++) {
+      for (int32_t i = 0; i < 4; i++) {
+        pixel[x * 4 + i] = uint8_t(color[i] * mask[x] + 0.5f);
+      }
+    }
+  }
+  return aDT->CreateSourceSurfaceFromData(&data.front(), aMask.mSize, stride,
+                                          FORMAT_B8G8R8A8);
+}
+
+/* Returns the cached mask for aKey, or the end of the cache. sMaskCacheLock must be held. */
+static std::list<BlurredMask>::iterator
+FindCachedMask(const MaskKey &aKey)
+{
+  std::list<BlurredMask>::iterator mask = sMaskCache.begin();
+  while (mask != sMaskCache.end() && !(mask->mKey == aKey)) {
+    ++mask;
+  }
+  return mask;
+}
+
+/**
+ * Returns the shadow surface for aKey in aColor, blurring its mask if it isn't cached.
+ * Only the cache is locked; the mask is copied out of it, and blurred and uploaded outside
+ * the lock, so that draw targets on other threads aren't held up behind them.
+ */
+static TemporaryRef<SourceSurface>
+GetShadowSurface(DrawTarget *aDT, const MaskKey &aKey, const Color &aColor)
+{
+  BlurredMask mask;
+  mask.mKey = aKey;
+
+  bool found;
+  {
+    MutexAutoLock lock(sMaskCacheLock);
+    std::list<BlurredMask>::iterator cached = FindCachedMask(aKey);
+    found = cached != sMaskCache.end();
+    if (found) {
+      sMaskCache.splice(sMaskCache.begin(), sMaskCache, cached);
+      mask = sMaskCache.front();
+    }
+  }
+
+  if (!found) {
+    CreateMask(mask);
+
+    MutexAutoLock lock(sMaskCacheLock);
+    // Another thread may have blurred the same mask in the meantime.
+    if (FindCachedMask(aKey) == sMaskCache.end()) {
+      sMaskCache.push_front(mask);
+      if (sMaskCache.size() > kMaxCachedMasks) {
+        sMaskCache.pop_back();
+      }
+    }
+  }
+
+  return CreateShadowSurface(aDT, mask, aColor);
+}
+
+static void
+AppendRectToPath(PathBuilder *aBuilder, const Rect &aRect)
+{
+  aBuilder->MoveTo(aRect.TopLeft());
+  aBuilder->LineTo(aRect.TopRight());
+  aBuilder->LineTo(aRect.BottomRight());
+  aBuilder->LineTo(aRect.BottomLeft());
+  aBuilder->Close();
+}
+
+static void
+AppendRoundedRectToPath(PathBuilder *aBuilder, const Rect &aRect, const RectCornerRadii &aRadii)
+{
+  // How far along its tangent each control point of a quarter ellipse is, as a fraction of
+  // the radius, subtracted from 1.
+  const Float k = 1.0f - 0.5522847498f;
+  const Size &tl = aRadii.mTopLeft;
+  const Size &tr = aRadii.mTopRight;
+  const Size &br = aRadii.mBottomRight;
+  const Size &bl = aRadii.mBottomLeft;
+
+  aBuilder->MoveTo(Point(aRect.x + tl.width, aRect.y));
+  aBuilder->LineTo(Point(aRect.XMost() - tr.width, aRect.y));
+  aBuilder->BezierTo(Point(aRect.XMost() - tr.width * k, aRect.y),
+                     Point(aRect.XMost(), aRect.y + tr.height * k),
+                     Point(aRect.XMost(), aRect.y + tr.height));
+  aBuilder->LineTo(Point(aRect.XMost(), aRect.YMost() - br.height));
+  aBuilder->BezierTo(Point(aRect.XMost(), aRect.YMost() - br.height * k),
+                     Point(aRect.XMost() - br.width * k, aRect.YMost()),
+                     Point(aRect.XMost() - br.width, aRect.YMost()));
+  aBuilder->LineTo(Point(aRect.x + bl.width, aRect.YMost()));
+  aBuilder->BezierTo(Point(aRect.x + bl.width * k, aRect.YMost()),
+                     Point(aRect.x, aRect.YMost() - bl.height * k),
+                     Point(aRect.x, aRect.YMost() - bl.height));
+  aBuilder->LineTo(Point(aRect.x, aRect.y + tl.height));
+  aBuilder->BezierTo(Point(aRect.x, aRect.y + tl.height * k),
+                     Point(aRect.x + tl.width * k, aRect.y),
+                     Point(aRect.x + tl.width, aRect.y));
+  aBuilder->Close();
+}
+
+/**
+ * Chooses the size of the mask's shape along one axis, and where its one pixel wide
+ * stretchable middle starts. aLength is the real size of the shape, and aStart and aEnd the
+ * space its corners and their blur need at either end.
+ */
+static void
+ChooseShapeLength(Float aLength, int32_t aStart, int32_t aEnd,
+                  int32_t &aShapeLength, int32_t &aMiddle)
+{
+  if (aLength >= aStart + 1 + aEnd) {
+    aShapeLength = aStart + 1 + aEnd;
+    aMiddle = aStart;
+  } else {
+    // Too small to stretch. The mask is drawn at its own size, give or take a pixel.
+    aShapeLength = int32_t(ceil(aLength));
+    aMiddle = (aShapeLength - 1) / 2;
+  }
+}
+
+/**
+ * Draws aSurface, a mask of size aMaskSize, over aDest. The row and column starting at
+ * aMiddle in the mask are stretched to make up the difference in size.
+ */
+static void
+DrawNinePatch(DrawTarget *aDT, SourceSurface *aSurface, const IntSize &aMaskSize,
+              const IntPoint &aMiddle, const Rect &aDest, const DrawOptions &aOptions)
+{
+  Float sourceX[4] = { 0, Float(aMiddle.x), Float(aMiddle.x + 1), Float(aMaskSize.width) };
+  Float sourceY[4] = { 0, Float(aMiddle.y), Float(aMiddle.y + 1), Float(aMaskSize.height) };
+  Float destX[4] = { aDest.x, aDest.x + aMiddle.x,
+                     aDest.XMost() - (aMaskSize.width - aMiddle.x - 1), aDest.XMost() };
+  Float destY[4] = { aDest.y, aDest.y + aMiddle.y,
+                     aDest.YMost() - (aMaskSize.height - aMiddle.y - 1), aDest.YMost() };
+
+  for (int32_t row = 0; row < 3; row++) {
+    for (int32_t column = 0; column < 3; column++) {
+      Rect source(sourceX[column], sourceY[row],
+                  sourceX[column + 1] - sourceX[column], sourceY[row + 1] - sourceY[row]);
+      Rect dest(destX[column], destY[row],
+                destX[column + 1] - destX[column], destY[row + 1] - destY[row]);
+      if (source.IsEmpty() || dest.IsEmpty()) {
+        continue;
+      }
+      // Bounded sampling keeps neighbouring patches from bleeding into stretched ones.
+      aDT->DrawSurface(aSurface, dest, source,
+                       DrawSurfaceOptions(FILTER_LINEAR, SAMPLING_BOUNDED), aOptions);
+    }
+  }
+}
+
+void
+DrawBoxShadow(DrawTarget *aDT,
+              const Rect &aRect,
+              const RectCornerRadii &aRadii,
+              const Color &aColor,
+              const Point &aOffset,
+              Float aSpread,
+              Float aBlurSigma,
+              bool aInset,
+              const Rect *aClip,
+              const DrawOptions &aOptions)
+{
+  if (aRect.IsEmpty()) {
+    return;
+  }
+
+  int32_t blurRadius = AlphaBoxBlur::CalculateBlurRadius(Point(aBlurSigma, aBlurSigma)).width;
+
+  RectCornerRadii boxRadii = aRadii;
+  ScaleRadiiToFit(boxRadii, aRect.Size());
+
+  // An inset shadow surrounds a hole that shrinks as the spread grows.
+  Float spread = aInset ? -aSpread : aSpread;
+  Rect shape = aRect;
+  shape.Inflate(spread);
+  shape.MoveBy(aOffset);
+  RectCornerRadii shapeRadii;
+  shapeRadii.mTopLeft = SpreadCornerRadius(boxRadii.mTopLeft, spread);
+  shapeRadii.mTopRight = SpreadCornerRadius(boxRadii.mTopRight, spread);
+  shapeRadii.mBottomRight = SpreadCornerRadius(boxRadii.mBottomRight, spread);
+  shapeRadii.mBottomLeft = SpreadCornerRadius(boxRadii.mBottomLeft, spread);
+
+  if (aClip) {
+    aDT->PushClipRect(*aClip);
+  }
+
+  // Outset shadows are only visible outside the box, and inset ones only inside it.
+  Rect shadowBounds = shape;
+  shadowBounds.Inflate(blurRadius);
+  RefPtr<PathBuilder> builder = aDT->CreatePathBuilder(FILL_EVEN_ODD);
+  if (!aInset) {
+    Rect outside = shadowBounds.Union(aRect);
+    outside.Inflate(1);
+    AppendRectToPath(builder, outside);
+  }
+  AppendRoundedRectToPath(builder, aRect, boxRadii);
+  RefPtr<Path> clipPath = builder->Finish();
+  aDT->PushClip(clipPath);
+
+  if (shape.width <= 0 || shape.height <= 0) {
+    // No outset shadow, or an inset one without a hole.
+    if (aInset) {
+      aDT->FillRect(aRect, ColorPattern(aColor), aOptions);
+    }
+  } else {
+    ScaleRadiiToFit(shapeRadii, shape.Size());
+
+    int32_t left = int32_t(ceil(max(shapeRadii.mTopLeft.width,
+                                    shapeRadii.mBottomLeft.width))) + blurRadius;
+    int32_t right = int32_t(ceil(max(shapeRadii.mTopRight.width,
+                                     shapeRadii.mBottomRight.width))) + blurRadius;
+    int32_t top = int32_t(ceil(max(shapeRadii.mTopLeft.height,
+                                   shapeRadii.mTopRight.height))) + blurRadius;
+    int32_t bottom = int32_t(ceil(max(shapeRadii.mBottomLeft.height,
+                                      shapeRadii.mBottomRight.height))) + blurRadius;
+
+    MaskKey key;
+    IntPoint middle;
+    ChooseShapeLength(shape.width, left, right, key.mShapeSize.width, middle.x);
+    ChooseShapeLength(shape.height, top, bottom, key.mShapeSize.height, middle.y);
+    key.mRadii = shapeRadii;
+    key.mBlurRadius = blurRadius;
+    key.mInset = aInset;
+
+    RefPtr<SourceSurface> surface = GetShadowSurface(aDT, key, aColor);
+    if (surface) {
+      // The middle is measured from the edge of the mask, which has the blur around it.
+      middle += IntPoint(blurRadius, blurRadius);
+      DrawNinePatch(aDT, surface, surface->GetSize(), middle, shadowBounds, aOptions);
+    }
+
+    if (aInset) {
+      // Outside the mask, an inset shadow is solid.
+      Rect bands[4] = {
+        Rect(aRect.x, aRect.y, aRect.width, shadowBounds.y - aRect.y),
+        Rect(aRect.x, shadowBounds.YMost(), aRect.width, aRect.YMost() - shadowBounds.YMost()),
+        Rect(aRect.x, shadowBounds.y, shadowBounds.x - aRect.x, shadowBounds.height),
+        Rect(shadowBounds.XMost(), shadowBounds.y,
+             aRect.XMost() - shadowBounds.XMost(), shadowBounds.height)
+      };
+      for (int i = 0; i < 4; i++) {
+        Rect band = bands[i].Intersect(aRect);
+        if (!band.IsEmpty()) {
+          aDT->FillRect(band, ColorPattern(aColor), aOptions);
+        }
+      }
+    }
+  }
+
+  aDT->PopClip();
+  if (aClip) {
+    aDT->PopClip();
+  }
+}
+
+}
+}
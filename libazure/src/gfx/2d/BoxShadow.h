@@ -0,0 +1,48 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#ifndef MOZILLA_GFX_BOXSHADOW_H_
+#define MOZILLA_GFX_BOXSHADOW_H_
+
+#include "2D.h"
+
+namespace mozilla {
+namespace gfx {
+
+/* The elliptical radii of the corners of a rounded rectangle. */
+struct RectCornerRadii
+{
+  Size mTopLeft;
+  Size mTopRight;
+  Size mBottomRight;
+  Size mBottomLeft;
+};
+
+/**
+ * Draws a CSS box-shadow for the rounded rectangle aRect.
+ *
+ * An outset shadow is the box grown by aSpread, moved by aOffset and blurred with a
+ * Gaussian of standard deviation aBlurSigma. It is not drawn inside the box itself. An
+ * inset shadow is drawn inside the box, around a hole that is the box shrunk by aSpread and
+ * moved by aOffset. If aClip is given, drawing is restricted to it.
+ *
+ * Rather than blurring a shadow the size of the box, the shadow is drawn as nine patches of
+ * a mask just large enough to hold its corners, with the edges and middle stretched. Blurred
+ * masks are cached by their shape and blur radius.
+ */
+GFX2D_API void DrawBoxShadow(DrawTarget *aDT,
+                             const Rect &aRect,
+                             const RectCornerRadii &aRadii,
+                             const Color &aColor,
+                             const Point &aOffset,
+                             Float aSpread,
+                             Float aBlurSigma,
+                             bool aInset,
+                             const Rect *aClip,
+                             const DrawOptions &aOptions = DrawOptions());
+
+}
+}
+
+#endif /* MOZILLA_GFX_BOXSHADOW_H_ */
@@ -0,0 +1,46 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "2D.h"
+#include "Tools.h"
+
+#include <string.h>
+
+namespace mozilla {
+namespace gfx {
+
+bool
+DrawTarget::ReadPixels(const IntRect &aRect, uint8_t *aData, int32_t aStride)
+{
+  RefPtr<SourceSurface> snapshot = Snapshot();
+  if (!snapshot) {
+    return false;
+  }
+  RefPtr<DataSourceSurface> data = snapshot->GetDataSurface();
+  if (!data || !IntRect(IntPoint(0, 0), data->GetSize()).Contains(aRect)) {
+    return false;
+  }
+
+  int32_t bytesPerPixel = BytesPerPixel(data->GetFormat());
+  uint8_t *source = data->GetData() + aRect.y * data->Stride() + aRect.x * bytesPerPixel;
+  for (int32_t y = 0; y < aRect.height; y++) {
+    memcpy(aData + y * aStride, source + y * data->Stride(), aRect.width * bytesPerPixel);
+  }
+  return true;
+}
+
+TemporaryRef<DataSourceSurface>
+DrawTarget::SnapshotRect(const IntRect &aRect)
+{
+  RefPtr<DataSourceSurface> surface =
+    Factory::CreateDataSourceSurface(aRect.Size(), GetFormat());
+  if (!surface || !ReadPixels(aRect, surface->GetData(), surface->Stride())) {
+    return nullptr;
+  }
+  return surface;
+}
+
+}
+}
@@ -347,6 +347,50 @@ DrawTargetCairo::Flush()
   cairo_surface_flush(surf);
 }
 
+bool
+DrawTargetCairo::ReadPixels(const IntRect &aRect, uint8_t *aData, int32_t aStride)
+{
+  if (!IntRect(IntPoint(0, 0), mSize).Contains(aRect)) {
+    return false;
+  }
+
+  cairo_surface_t* surf = cairo_get_target(mContext);
+  cairo_surface_flush(surf);
+
+  // Surfaces that aren't in memory are first drawn into an image surface the
+  // size of aRect.
+  cairo_surface_t* image;
+  IntPoint origin = aRect.TopLeft();
+  if (cairo_surface_get_type(surf) == CAIRO_SURFACE_TYPE_IMAGE) {
+    image = cairo_surface_reference(surf);
+  } else {
+    image = cairo_image_surface_create(GfxFormatToCairoFormat(mFormat),
+                                       aRect.width, aRect.height);
+    if (cairo_surface_status(image)) {
+      cairo_surface_destroy(image);
+      return false;
+    }
+    cairo_t* ctx = cairo_create(image);
+    cairo_set_operator(ctx, CAIRO_OPERATOR_SOURCE);
+    cairo_set_source_surface(ctx, surf, -aRect.x, -aRect.y);
+    cairo_paint(ctx);
+    cairo_destroy(ctx);
+    cairo_surface_flush(image);
+    origin = IntPoint(0, 0);
+  }
+
+  unsigned char* data = cairo_image_surface_get_data(image);
+  int32_t stride = cairo_image_surface_get_stride(image);
+  int32_t bytesPerPixel = BytesPerPixel(mFormat);
+  for (int32_t y = 0; y < aRect.height; y++) {
+    memcpy(aData + y * aStride,
+           data + (origin.y + y) * stride + origin.x * bytesPerPixel,
+           aRect.width * bytesPerPixel);
+  }
+  cairo_surface_destroy(image);
+  return true;
+}
+
 void
 DrawTargetCairo::PrepareForDrawing(cairo_t* aContext, const Path* aPath /* = nullptr */)
 {
@@ -787,9 +831,16 @@ DrawTargetCairo::CreateSourceSurfaceFromData(unsigned char *aData,
     return nullptr;
   }
 
-  CopyDataToCairoSurface(surf, aData, aSize, aStride, BytesPerPixel(aFormat));
+  if (ToBGRAFormat(aFormat) != aFormat) {
+    ConvertToBGRA(aData, aStride, aFormat, cairo_image_surface_get_data(surf),
+                  cairo_image_surface_get_stride(surf), aSize);
+    cairo_surface_mark_dirty(surf);
+  } else {
+    CopyDataToCairoSurface(surf, aData, aSize, aStride, BytesPerPixel(aFormat));
+  }
 
-  RefPtr<SourceSurfaceCairo> source_surf = new SourceSurfaceCairo(surf, aSize, aFormat);
+  RefPtr<SourceSurfaceCairo> source_surf =
+    new SourceSurfaceCairo(surf, aSize, ToBGRAFormat(aFormat));
   cairo_surface_destroy(surf);
 
   return source_surf;
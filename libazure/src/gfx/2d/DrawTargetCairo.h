@@ -59,6 +59,7 @@ public:
   virtual IntSize GetSize();
 
   virtual void Flush();
+  virtual bool ReadPixels(const IntRect &aRect, uint8_t *aData, int32_t aStride);
   virtual void DrawSurface(SourceSurface *aSurface,
                            const Rect &aDest,
                            const Rect &aSource,
@@ -256,6 +256,30 @@ DrawTargetSkia::Flush()
   mCanvas->flush();
 }
 
+bool
+DrawTargetSkia::ReadPixels(const IntRect &aRect, uint8_t *aData, int32_t aStride)
+{
+  if (!IntRect(IntPoint(0, 0), mSize).Contains(aRect)) {
+    return false;
+  }
+
+  SkAutoLockPixels lock(mBitmap);
+  if (!mBitmap.getPixels()) {
+    // GPU draw targets have no pixels in memory, so the canvas reads them back.
+    SkBitmap bitmap;
+    bitmap.setConfig(GfxFormatToSkiaConfig(mFormat), aRect.width, aRect.height, aStride);
+    bitmap.setPixels(aData);
+    return mCanvas->readPixels(&bitmap, aRect.x, aRect.y);
+  }
+
+  int32_t bytesPerPixel = mBitmap.bytesPerPixel();
+  for (int32_t y = 0; y < aRect.height; y++) {
+    memcpy(aData + y * aStride, mBitmap.getAddr(aRect.x, aRect.y + y),
+           aRect.width * bytesPerPixel);
+  }
+  return true;
+}
+
 void
 DrawTargetSkia::DrawSurface(SourceSurface *aSurface,
                             const Rect &aDest,
@@ -445,7 +469,7 @@ DrawTargetSkia::FillGlyphs(ScaledFont *aFont,
                            const GlyphBuffer &aBuffer,
                            const Pattern &aPattern,
                            const DrawOptions &aOptions,
-                           const GlyphRenderingOptions*)
+                           const GlyphRenderingOptions *aRenderingOptions)
 {
   if (aFont->GetType() != FONT_MAC &&
       aFont->GetType() != FONT_SKIA &&
@@ -458,23 +482,57 @@ DrawTargetSkia::FillGlyphs(ScaledFont *aFont,
   ScaledFontBase* skiaFont = static_cast<ScaledFontBase*>(aFont);
 
   AutoPaintSetup paint(mCanvas.get(), aOptions, aPattern);
-  paint.mPaint.setTypeface(skiaFont->GetSkTypeface());
-  paint.mPaint.setTextSize(SkFloatToScalar(skiaFont->mSize));
-  paint.mPaint.setTextEncoding(SkPaint::kGlyphID_TextEncoding);
+  skiaFont->InitSkiaPaint(paint.mPaint);
   paint.mPaint.setSubpixelText(true);
-  
+  bool colorGlyphs = false;
+  if (aRenderingOptions && aRenderingOptions->GetType() == FONT_SKIA) {
+    const GlyphRenderingOptionsSkia* skiaOptions =
+      static_cast<const GlyphRenderingOptionsSkia*>(aRenderingOptions);
+    skiaOptions->ApplyToPaint(paint.mPaint);
+    colorGlyphs = skiaOptions->GetColorGlyphs();
+  }
+
+  // Color bitmap glyphs are drawn as images, either in their own colours or as a mask
+  // filled with the pattern. Neither wants the coverage mask filter of the text paint.
+  SkPaint bitmapPaint(paint.mPaint);
+  bitmapPaint.setMaskFilter(nullptr);
+  if (colorGlyphs) {
+    bitmapPaint.setShader(nullptr);
+    bitmapPaint.setColor(SkColorSetARGB(U8CPU(paint.mAlpha * 255.0), 0, 0, 0));
+  }
+
   std::vector<uint16_t> indices;
   std::vector<SkPoint> offsets;
-  indices.resize(aBuffer.mNumGlyphs);
-  offsets.resize(aBuffer.mNumGlyphs);
+  indices.reserve(aBuffer.mNumGlyphs);
+  offsets.reserve(aBuffer.mNumGlyphs);
 
+  SkBitmap glyphBitmap;
+  Rect glyphBounds;
   for (unsigned int i = 0; i < aBuffer.mNumGlyphs; i++) {
-    indices[i] = aBuffer.mGlyphs[i].mIndex;
-    offsets[i].fX = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.x);
-    offsets[i].fY = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.y);
+    const Glyph &glyph = aBuffer.mGlyphs[i];
+    if (skiaFont->GetColorGlyph(glyph.mIndex, glyphBitmap, glyphBounds)) {
+      glyphBounds.MoveBy(glyph.mPosition);
+      if (colorGlyphs) {
+        mCanvas->drawBitmapRectToRect(glyphBitmap, nullptr, RectToSkRect(glyphBounds),
+                                      &bitmapPaint);
+      } else {
+        // Skia fills A8 bitmaps with the paint's color or shader.
+        SkBitmap glyphAlpha;
+        glyphBitmap.extractAlpha(&glyphAlpha);
+        mCanvas->drawBitmapRectToRect(glyphAlpha, nullptr, RectToSkRect(glyphBounds),
+                                      &bitmapPaint);
+      }
+      continue;
+    }
+
+    indices.push_back(glyph.mIndex);
+    offsets.push_back(SkPoint::Make(SkFloatToScalar(glyph.mPosition.x),
+                                    SkFloatToScalar(glyph.mPosition.y)));
   }
 
-  mCanvas->drawPosText(&indices.front(), aBuffer.mNumGlyphs*2, &offsets.front(), paint.mPaint);
+  if (!indices.empty()) {
+    mCanvas->drawPosText(&indices.front(), indices.size()*2, &offsets.front(), paint.mPaint);
+  }
 }
 
 void
@@ -33,6 +33,7 @@ public:
   virtual TemporaryRef<SourceSurface> Snapshot();
   virtual IntSize GetSize() { return mSize; }
   virtual void Flush();
+  virtual bool ReadPixels(const IntRect &aRect, uint8_t *aData, int32_t aStride);
   virtual void DrawSurface(SourceSurface *aSurface,
                            const Rect &aDest,
                            const Rect &aSource,
@@ -7,6 +7,7 @@
 
 #ifdef USE_CAIRO
 #include "DrawTargetCairo.h"
+#include "HelpersCairo.h"
 #include "ScaledFontBase.h"
 #endif
 
@@ -45,6 +46,7 @@
 #include "DrawEventRecorder.h"
 
 #include "Logging.h"
+#include "Tools.h"
 
 #ifdef PR_LOGGING
 PRLogModuleInfo *
@@ -153,9 +155,17 @@ ID3D10Device1 *Factory::mD3D10Device;
 #endif
 
 DrawEventRecorder *Factory::mRecorder;
+// Read and written atomically, since tests flip it while other threads may be drawing.
+bool Factory::mSSE2Disabled = false;
 
 bool
 Factory::HasSSE2()
+{
+  return !__atomic_load_n(&mSSE2Disabled, __ATOMIC_RELAXED) && CPUSupportsSSE2();
+}
+
+bool
+Factory::CPUSupportsSSE2()
 {
 #if defined(__SSE2__) || defined(_M_X64) || \
     (defined(_M_IX86_FP) && _M_IX86_FP >= 2)
@@ -169,9 +179,18 @@ Factory::HasSSE2()
 #endif
 }
 
+void
+Factory::SetSSE2Disabled(bool aDisabled)
+{
+  __atomic_store_n(&mSSE2Disabled, aDisabled, __ATOMIC_RELAXED);
+}
+
 TemporaryRef<DrawTarget>
 Factory::CreateDrawTarget(BackendType aBackend, const IntSize &aSize, SurfaceFormat aFormat)
 {
+  // Draw targets can only render in formats the backends understand.
+  SurfaceFormat format = ToBGRAFormat(aFormat);
+
   RefPtr<DrawTarget> retVal;
   switch (aBackend) {
 #ifdef WIN32
@@ -179,7 +198,7 @@ Factory::CreateDrawTarget(BackendType aBackend, const IntSize &aSize, SurfaceFor
     {
       RefPtr<DrawTargetD2D> newTarget;
       newTarget = new DrawTargetD2D();
-      if (newTarget->Init(aSize, aFormat)) {
+      if (newTarget->Init(aSize, format)) {
         retVal = newTarget;
       }
       break;
@@ -190,7 +209,7 @@ Factory::CreateDrawTarget(BackendType aBackend, const IntSize &aSize, SurfaceFor
     {
       RefPtr<DrawTargetCG> newTarget;
       newTarget = new DrawTargetCG();
-      if (newTarget->Init(aBackend, aSize, aFormat)) {
+      if (newTarget->Init(aBackend, aSize, format)) {
         retVal = newTarget;
       }
       break;
@@ -201,9 +220,22 @@ Factory::CreateDrawTarget(BackendType aBackend, const IntSize &aSize, SurfaceFor
     {
       RefPtr<DrawTargetSkia> newTarget;
       newTarget = new DrawTargetSkia();
-      if (newTarget->Init(aSize, aFormat)) {
+      if (newTarget->Init(aSize, format)) {
+        retVal = newTarget;
+      }
+      break;
+    }
+#endif
+#ifdef USE_CAIRO
+  case BACKEND_CAIRO:
+    {
+      cairo_surface_t* surf = cairo_image_surface_create(GfxFormatToCairoFormat(format),
+                                                         aSize.width, aSize.height);
+      RefPtr<DrawTargetCairo> newTarget = new DrawTargetCairo();
+      if (newTarget->Init(surf, aSize)) {
         retVal = newTarget;
       }
+      cairo_surface_destroy(surf);
       break;
     }
 #endif
@@ -241,6 +273,11 @@ Factory::CreateDrawTargetForData(BackendType aBackend,
 {
   RefPtr<DrawTarget> retVal;
 
+  if (ToBGRAFormat(aFormat) != aFormat) {
+    gfxDebug() << "Draw targets can't render into format " << aFormat;
+    return nullptr;
+  }
+
   switch (aBackend) {
 #ifdef USE_SKIA
   case BACKEND_SKIA:
@@ -259,6 +296,20 @@ Factory::CreateDrawTargetForData(BackendType aBackend,
         return newTarget;
       break;
     }
+#endif
+#ifdef USE_CAIRO
+  case BACKEND_CAIRO:
+    {
+      cairo_surface_t* surf =
+        cairo_image_surface_create_for_data(aData, GfxFormatToCairoFormat(aFormat),
+                                            aSize.width, aSize.height, aStride);
+      RefPtr<DrawTargetCairo> newTarget = new DrawTargetCairo();
+      bool initialized = newTarget->Init(surf, aSize);
+      cairo_surface_destroy(surf);
+      if (initialized)
+        return newTarget;
+      break;
+    }
 #endif
   default:
     gfxDebug() << "Invalid draw target type specified.";
@@ -322,17 +373,40 @@ Factory::CreateScaledFontForNativeFont(const NativeFont &aNativeFont, Float aSiz
 TemporaryRef<ScaledFont>
 Factory::CreateScaledFontForTrueTypeData(uint8_t *aData, uint32_t aSize,
                                          uint32_t aFaceIndex, Float aGlyphSize,
-                                         FontType aType)
+                                         FontType aType, FontDataReleaseFunc aRelease,
+                                         void *aClosure)
 {
   switch (aType) {
 #ifdef WIN32
   case FONT_DWRITE:
     {
-      return new ScaledFontDWrite(aData, aSize, aFaceIndex, aGlyphSize);
+      RefPtr<ScaledFont> font = new ScaledFontDWrite(aData, aSize, aFaceIndex, aGlyphSize);
+      if (aRelease) {
+        aRelease(aClosure);
+      }
+      return font;
+    }
+#endif
+#ifdef USE_SKIA
+#ifdef MOZ_ENABLE_FREETYPE
+  case FONT_SKIA:
+    {
+      // The font takes over releasing the data, even if it fails.
+      RefPtr<ScaledFontFreetype> font =
+        new ScaledFontFreetype(aData, aSize, aFaceIndex, aGlyphSize, aRelease, aClosure);
+      if (!font->GetSkTypeface()) {
+        gfxWarning() << "Unable to create a typeface from truetype data";
+        return nullptr;
+      }
+      return font;
     }
+#endif
 #endif
   default:
     gfxWarning() << "Unable to create requested font type from truetype data";
+    if (aRelease) {
+      aRelease(aClosure);
+    }
     return nullptr;
   }
 }
@@ -479,6 +553,28 @@ Factory::CreateDrawTargetForCairoSurface(cairo_surface_t* aSurface, const IntSiz
   return retVal;
 }
 
+TemporaryRef<DataSourceSurface>
+Factory::CreateDataSourceSurface(const IntSize &aSize, SurfaceFormat aFormat)
+{
+  if (aSize.width < 0 || aSize.height < 0) {
+    gfxWarning() << "Invalid size for data source surface";
+    return nullptr;
+  }
+
+  int32_t stride = GetAlignedStride<4>(aSize.width * BytesPerPixel(aFormat));
+  uint8_t *data = new uint8_t[stride * aSize.height];
+  memset(data, 0, stride * aSize.height);
+
+  RefPtr<SourceSurfaceRawData> newSurf = new SourceSurfaceRawData();
+
+  if (newSurf->InitWrappingData(data, aSize, stride, aFormat, true)) {
+    return newSurf;
+  }
+
+  delete [] data;
+  return nullptr;
+}
+
 TemporaryRef<DataSourceSurface>
 Factory::CreateWrappingDataSourceSurface(uint8_t *aData, int32_t aStride,
                                          const IntSize &aSize,
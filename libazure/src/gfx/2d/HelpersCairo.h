@@ -120,6 +120,11 @@ GfxFormatToCairoFormat(SurfaceFormat format)
       return CAIRO_FORMAT_A8;
     case FORMAT_R5G6B5:
       return CAIRO_FORMAT_RGB16_565;
+    case FORMAT_R8G8B8A8:
+    case FORMAT_R16G16B16A16:
+      return CAIRO_FORMAT_ARGB32;
+    case FORMAT_R8G8B8X8:
+      return CAIRO_FORMAT_RGB24;
     default:
       gfxWarning() << "Unknown image format";
       return CAIRO_FORMAT_ARGB32;
@@ -132,8 +137,11 @@ GfxFormatToCairoContent(SurfaceFormat format)
   switch (format)
   {
     case FORMAT_B8G8R8A8:
+    case FORMAT_R8G8B8A8:
+    case FORMAT_R16G16B16A16:
       return CAIRO_CONTENT_COLOR_ALPHA;
     case FORMAT_B8G8R8X8:
+    case FORMAT_R8G8B8X8:
     case FORMAT_R5G6B5:  //fall through
       return CAIRO_CONTENT_COLOR;
     case FORMAT_A8:
@@ -33,7 +33,10 @@ GfxFormatToSkiaConfig(SurfaceFormat format)
       return SkBitmap::kRGB_565_Config;
     case FORMAT_A8:
       return SkBitmap::kA8_Config;
-
+    case FORMAT_R8G8B8A8:
+    case FORMAT_R8G8B8X8:
+    case FORMAT_R16G16B16A16:
+      return SkBitmap::kARGB_8888_Config;
   }
 
   return SkBitmap::kARGB_8888_Config;
@@ -54,6 +57,9 @@ GfxFormatToGrConfig(SurfaceFormat format)
       return kRGB_565_GrPixelConfig;
     case FORMAT_A8:
       return kAlpha_8_GrPixelConfig;
+    case FORMAT_R8G8B8A8:
+    case FORMAT_R8G8B8X8:
+      return kRGBA_8888_GrPixelConfig;
     default:
       return kRGBA_8888_GrPixelConfig;
   }
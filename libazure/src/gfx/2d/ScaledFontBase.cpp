@@ -9,12 +9,14 @@
 #include "PathSkia.h"
 #include "SkPaint.h"
 #include "SkPath.h"
+#include "SkTableMaskFilter.h"
 #endif
 
 #ifdef USE_CAIRO
 #include "PathCairo.h"
 #endif
 
+#include <algorithm>
 #include <vector>
 #include <cmath>
 
@@ -23,6 +25,47 @@ using namespace std;
 namespace mozilla {
 namespace gfx {
 
+#ifdef USE_SKIA
+static SkPaint::Hinting
+FontHintingToSkia(FontHinting aHinting)
+{
+  switch (aHinting) {
+  case FONT_HINTING_NONE:
+    return SkPaint::kNo_Hinting;
+  case FONT_HINTING_LIGHT:
+    return SkPaint::kSlight_Hinting;
+  case FONT_HINTING_NORMAL:
+    return SkPaint::kNormal_Hinting;
+  case FONT_HINTING_FULL:
+    return SkPaint::kFull_Hinting;
+  }
+  return SkPaint::kNormal_Hinting;
+}
+
+void
+GlyphRenderingOptionsSkia::ApplyToPaint(SkPaint &aPaint) const
+{
+  aPaint.setHinting(FontHintingToSkia(mHinting));
+  aPaint.setAutohinted(mAutohinter);
+  aPaint.setSubpixelText(mSubpixelPositioning);
+  aPaint.setLCDRenderText(mLCDText);
+
+  if (mLCDText || (mGamma == 1.0f && mContrast == 0.0f)) {
+    return;
+  }
+
+  uint8_t table[256];
+  for (int i = 0; i < 256; i++) {
+    Float coverage = pow(i / 255.0f, 1.0f / mGamma);
+    coverage = (coverage - 0.5f) * (1.0f + mContrast) + 0.5f;
+    table[i] = uint8_t(std::min(std::max(coverage, 0.0f), 1.0f) * 255.0f + 0.5f);
+  }
+  // Keep fully uncovered pixels untouched, so contrast doesn't fill the glyph boxes.
+  table[0] = 0;
+  SkSafeUnref(aPaint.setMaskFilter(new SkTableMaskFilter(table)));
+}
+#endif
+
 ScaledFontBase::~ScaledFontBase()
 {
 #ifdef USE_SKIA
@@ -35,6 +78,8 @@ ScaledFontBase::~ScaledFontBase()
 
 ScaledFontBase::ScaledFontBase(Float aSize)
   : mSize(aSize)
+  , mSyntheticBold(false)
+  , mSyntheticOblique(false)
 {
 #ifdef USE_SKIA
   mTypeface = nullptr;
@@ -44,29 +89,59 @@ ScaledFontBase::ScaledFontBase(Float aSize)
 #endif
 }
 
+#ifdef USE_SKIA
+SkPath
+ScaledFontBase::GetSkiaPathForGlyphs(const GlyphBuffer &aBuffer)
+{
+  SkPath path;
+  if (!aBuffer.mNumGlyphs) {
+    return path;
+  }
+
+  SkPaint paint;
+  InitSkiaPaint(paint);
+
+  std::vector<uint16_t> indices;
+  std::vector<SkPoint> offsets;
+  indices.resize(aBuffer.mNumGlyphs);
+  offsets.resize(aBuffer.mNumGlyphs);
+
+  for (unsigned int i = 0; i < aBuffer.mNumGlyphs; i++) {
+    indices[i] = aBuffer.mGlyphs[i].mIndex;
+    offsets[i].fX = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.x);
+    offsets[i].fY = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.y);
+  }
+
+  paint.getPosTextPath(&indices.front(), aBuffer.mNumGlyphs*2, &offsets.front(), &path);
+  return path;
+}
+
+void
+ScaledFontBase::InitSkiaPaint(SkPaint &aPaint)
+{
+  aPaint.setTypeface(GetSkTypeface());
+  aPaint.setTextEncoding(SkPaint::kGlyphID_TextEncoding);
+  aPaint.setTextSize(SkFloatToScalar(mSize));
+  aPaint.setFakeBoldText(mSyntheticBold);
+  if (mSyntheticOblique) {
+    // The same skew that Skia uses for synthetic italics.
+    aPaint.setTextSkewX(-SK_Scalar1 / 4);
+  }
+}
+
+static inline Point
+SkPointToPoint(const SkPoint &aPoint)
+{
+  return Point(SkScalarToFloat(aPoint.fX), SkScalarToFloat(aPoint.fY));
+}
+#endif
+
 TemporaryRef<Path>
 ScaledFontBase::GetPathForGlyphs(const GlyphBuffer &aBuffer, const DrawTarget *aTarget)
 {
 #ifdef USE_SKIA
-  if (aTarget->GetType() == BACKEND_SKIA) {
-    SkPaint paint;
-    paint.setTypeface(GetSkTypeface());
-    paint.setTextEncoding(SkPaint::kGlyphID_TextEncoding);
-    paint.setTextSize(SkFloatToScalar(mSize));
-
-    std::vector<uint16_t> indices;
-    std::vector<SkPoint> offsets;
-    indices.resize(aBuffer.mNumGlyphs);
-    offsets.resize(aBuffer.mNumGlyphs);
-
-    for (unsigned int i = 0; i < aBuffer.mNumGlyphs; i++) {
-      indices[i] = aBuffer.mGlyphs[i].mIndex;
-      offsets[i].fX = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.x);
-      offsets[i].fY = SkFloatToScalar(aBuffer.mGlyphs[i].mPosition.y);
-    }
-
-    SkPath path;
-    paint.getPosTextPath(&indices.front(), aBuffer.mNumGlyphs*2, &offsets.front(), &path);
+  if (aTarget->GetType() == BACKEND_SKIA && GetSkTypeface()) {
+    SkPath path = GetSkiaPathForGlyphs(aBuffer);
     return new PathSkia(path, FILL_WINDING);
   }
 #endif
@@ -101,9 +176,122 @@ ScaledFontBase::GetPathForGlyphs(const GlyphBuffer &aBuffer, const DrawTarget *a
 void
 ScaledFontBase::CopyGlyphsToBuilder(const GlyphBuffer &aBuffer, PathBuilder *aBuilder)
 {
-  // XXX - implement me
-  MOZ_ASSERT(false);
-  return;
+#ifdef USE_SKIA
+  // The outlines come from the typeface, so this works for builders of any backend.
+  if (GetSkTypeface()) {
+    SkPath path = GetSkiaPathForGlyphs(aBuffer);
+    SkPath::Iter iter(path, false);
+    SkPoint points[4];
+    SkPath::Verb verb;
+    while ((verb = iter.next(points)) != SkPath::kDone_Verb) {
+      switch (verb) {
+        case SkPath::kMove_Verb:
+          aBuilder->MoveTo(SkPointToPoint(points[0]));
+          break;
+        case SkPath::kLine_Verb:
+          aBuilder->LineTo(SkPointToPoint(points[1]));
+          break;
+        case SkPath::kQuad_Verb:
+          aBuilder->QuadraticBezierTo(SkPointToPoint(points[1]), SkPointToPoint(points[2]));
+          break;
+        case SkPath::kCubic_Verb:
+          aBuilder->BezierTo(SkPointToPoint(points[1]),
+                             SkPointToPoint(points[2]),
+                             SkPointToPoint(points[3]));
+          break;
+        case SkPath::kClose_Verb:
+          aBuilder->Close();
+          break;
+        default:
+          break;
+      }
+    }
+    return;
+  }
+#endif
+  // XXX - implement me for fonts without a Skia typeface. Until then the builder is left
+  // unchanged; callers can check GetSkTypeface() first.
+}
+
+bool
+ScaledFontBase::GetMetrics(FontMetrics &aMetrics)
+{
+#ifdef USE_SKIA
+  SkTypeface *typeface = GetSkTypeface();
+  if (!typeface) {
+    return false;
+  }
+
+  SkPaint paint;
+  InitSkiaPaint(paint);
+  SkPaint::FontMetrics metrics;
+  paint.getFontMetrics(&metrics);
+  aMetrics.mAscent = -SkScalarToFloat(metrics.fAscent);
+  aMetrics.mDescent = SkScalarToFloat(metrics.fDescent);
+  aMetrics.mLeading = SkScalarToFloat(metrics.fLeading);
+  aMetrics.mXHeight = SkScalarToFloat(metrics.fXHeight);
+
+  // Skia doesn't report underline metrics, so read them from the 'post' table, falling back
+  // to values derived from the descent for fonts without one.
+  aMetrics.mUnderlinePosition = aMetrics.mDescent / 2;
+  aMetrics.mUnderlineThickness = mSize / 14;
+  const SkFontTableTag kPostTag = SkSetFourByteTag('p', 'o', 's', 't');
+  uint8_t post[12];
+  int unitsPerEm = typeface->getUnitsPerEm();
+  if (unitsPerEm > 0 &&
+      typeface->getTableData(kPostTag, 0, sizeof(post), post) == sizeof(post)) {
+    int16_t position = int16_t((post[8] << 8) | post[9]);
+    int16_t thickness = int16_t((post[10] << 8) | post[11]);
+    // The 'post' table measures the underline position upwards from the baseline.
+    aMetrics.mUnderlinePosition = -position * mSize / unitsPerEm;
+    aMetrics.mUnderlineThickness = thickness * mSize / unitsPerEm;
+  }
+  return true;
+#else
+  return false;
+#endif
+}
+
+bool
+ScaledFontBase::GetGlyphMetrics(uint32_t aGlyph, Float *aAdvance, Rect *aBounds)
+{
+#ifdef USE_SKIA
+  if (!GetSkTypeface()) {
+    return false;
+  }
+
+  SkPaint paint;
+  InitSkiaPaint(paint);
+  uint16_t glyph = aGlyph;
+  SkScalar advance;
+  SkRect bounds;
+  paint.getTextWidths(&glyph, sizeof(glyph), &advance, &bounds);
+  *aAdvance = SkScalarToFloat(advance);
+  *aBounds = Rect(SkScalarToFloat(bounds.fLeft), SkScalarToFloat(bounds.fTop),
+                  SkScalarToFloat(bounds.width()), SkScalarToFloat(bounds.height()));
+  return true;
+#else
+  return false;
+#endif
+}
+
+uint32_t
+ScaledFontBase::GetGlyphIndex(uint32_t aCharacter)
+{
+#ifdef USE_SKIA
+  if (!GetSkTypeface()) {
+    return 0;
+  }
+
+  SkPaint paint;
+  InitSkiaPaint(paint);
+  paint.setTextEncoding(SkPaint::kUTF32_TextEncoding);
+  uint16_t glyph = 0;
+  paint.textToGlyphs(&aCharacter, sizeof(aCharacter), &glyph);
+  return glyph;
+#else
+  return 0;
+#endif
 }
 
 #ifdef USE_CAIRO
@@ -9,6 +9,9 @@
 #include "2D.h"
 
 #ifdef USE_SKIA
+#include "SkBitmap.h"
+#include "SkPaint.h"
+#include "SkPath.h"
 #include "SkTypeface.h"
 #endif
 #ifdef USE_CAIRO
@@ -20,6 +23,61 @@ class gfxFont;
 namespace mozilla {
 namespace gfx {
 
+/* Vertical metrics of a font at its size, in user space units. Ascent, descent and the
+ * underline position are distances from the baseline, positive upwards for the ascent and
+ * downwards for the others. */
+struct FontMetrics
+{
+  Float mAscent;
+  Float mDescent;
+  Float mLeading;
+  Float mXHeight;
+  Float mUnderlinePosition;
+  Float mUnderlineThickness;
+};
+
+#ifdef USE_SKIA
+/* Controls how DrawTargetSkia rasterizes glyphs. Gamma and contrast adjust the glyph
+ * coverage: a gamma above 1 darkens text and a contrast above 0 sharpens its edges. They
+ * are applied as a mask filter, which Skia doesn't support for LCD text, so they are
+ * ignored when aLCDText is set.
+ *
+ * Color bitmap glyphs, such as emoji, are drawn as images rather than through the paint.
+ * By default only their alpha is used, filled with the pattern like any other glyph;
+ * aColorGlyphs draws them in their own colours instead. */
+class GlyphRenderingOptionsSkia : public GlyphRenderingOptions
+{
+public:
+  GlyphRenderingOptionsSkia(FontHinting aHinting, bool aAutohinter,
+                            bool aSubpixelPositioning, bool aLCDText,
+                            Float aGamma, Float aContrast, bool aColorGlyphs)
+    : mHinting(aHinting)
+    , mAutohinter(aAutohinter)
+    , mSubpixelPositioning(aSubpixelPositioning)
+    , mLCDText(aLCDText)
+    , mGamma(aGamma)
+    , mContrast(aContrast)
+    , mColorGlyphs(aColorGlyphs)
+  {}
+
+  virtual FontType GetType() const { return FONT_SKIA; }
+
+  /* Applies these options to a paint set up for drawing glyphs. */
+  void ApplyToPaint(SkPaint &aPaint) const;
+
+  bool GetColorGlyphs() const { return mColorGlyphs; }
+
+private:
+  FontHinting mHinting;
+  bool mAutohinter;
+  bool mSubpixelPositioning;
+  bool mLCDText;
+  Float mGamma;
+  Float mContrast;
+  bool mColorGlyphs;
+};
+#endif
+
 class ScaledFontBase : public ScaledFont
 {
 public:
@@ -34,8 +92,31 @@ public:
 
 #ifdef USE_SKIA
   virtual SkTypeface* GetSkTypeface() { return mTypeface; }
+
+  /* Fonts with color bitmap glyphs (CBDT or sbix tables) return true for those glyphs,
+   * with the premultiplied image of the glyph in aBitmap and the rect it covers relative to
+   * the glyph origin in aBounds. */
+  virtual bool GetColorGlyph(uint32_t aGlyph, SkBitmap &aBitmap, Rect &aBounds)
+  {
+    return false;
+  }
 #endif
 
+  /* The metric queries below read the Skia typeface, and return false for fonts without
+   * one. */
+  bool GetMetrics(FontMetrics &aMetrics);
+  bool GetGlyphMetrics(uint32_t aGlyph, Float *aAdvance, Rect *aBounds);
+  /* Returns 0, the missing glyph, for characters the font doesn't cover. */
+  uint32_t GetGlyphIndex(uint32_t aCharacter);
+
+  /* Emboldens and/or slants the glyphs when drawing and measuring them, for faces that
+   * lack a real bold or italic variant. */
+  void SetSyntheticStyle(bool aBold, bool aOblique)
+  {
+    mSyntheticBold = aBold;
+    mSyntheticOblique = aOblique;
+  }
+
   // Not true, but required to instantiate a ScaledFontBase.
   virtual FontType GetType() const { return FONT_SKIA; }
 
@@ -46,6 +127,10 @@ public:
 
 protected:
   friend class DrawTargetSkia;
+#ifdef USE_SKIA
+  SkPath GetSkiaPathForGlyphs(const GlyphBuffer &aBuffer);
+  void InitSkiaPaint(SkPaint &aPaint);
+#endif
 #ifdef USE_SKIA
   SkTypeface* mTypeface;
 #endif
@@ -53,6 +138,8 @@ protected:
   cairo_scaled_font_t* mScaledFont;
 #endif
   Float mSize;
+  bool mSyntheticBold;
+  bool mSyntheticOblique;
 };
 
 }
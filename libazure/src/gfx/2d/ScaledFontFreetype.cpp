@@ -5,13 +5,19 @@
 
 #include "ScaledFontFreetype.h"
 #include "Logging.h"
+#include "mozilla/Mutex.h"
 
 #ifdef USE_SKIA
+#include "SkData.h"
 #include "SkTypeface.h"
 #include "SkStream.h"
 #endif
 
+#include <stdio.h>
+#include <string.h>
+#include <map>
 #include <string>
+#include <vector>
 
 using namespace std;
 
@@ -38,14 +44,347 @@ fontStyleToSkia(FontStyle aStyle)
 }
 #endif
 
+#ifdef USE_SKIA
+static uint32_t
+ReadBigEndian32(const uint8_t *aData)
+{
+  return (uint32_t(aData[0]) << 24) | (uint32_t(aData[1]) << 16) |
+         (uint32_t(aData[2]) << 8) | uint32_t(aData[3]);
+}
+
+static uint16_t
+ReadBigEndian16(const uint8_t *aData)
+{
+  return (uint16_t(aData[0]) << 8) | uint16_t(aData[1]);
+}
+
+static void
+WriteBigEndian32(uint8_t *aData, uint32_t aValue)
+{
+  aData[0] = aValue >> 24;
+  aData[1] = aValue >> 16;
+  aData[2] = aValue >> 8;
+  aData[3] = aValue;
+}
+
+/* Copies the face at aFaceIndex out of a TrueType collection into a standalone sfnt, since
+ * SkTypeface::CreateFromStream always loads the first face. Returns false if aData isn't a
+ * collection containing that face. */
+static bool
+ExtractCollectionFace(const uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex,
+                      std::vector<uint8_t> &aFace)
+{
+  const uint32_t kCollectionHeaderSize = 12;
+  const uint32_t kOffsetTableSize = 12;
+  const uint32_t kTableRecordSize = 16;
+
+  if (aSize < kCollectionHeaderSize || ReadBigEndian32(aData) != 0x74746366 /* 'ttcf' */) {
+    return false;
+  }
+  uint32_t numFonts = ReadBigEndian32(aData + 8);
+  if (aFaceIndex >= numFonts || kCollectionHeaderSize + 4 * (aFaceIndex + 1) > aSize) {
+    return false;
+  }
+
+  uint32_t offsetTable = ReadBigEndian32(aData + kCollectionHeaderSize + 4 * aFaceIndex);
+  if (offsetTable > aSize || aSize - offsetTable < kOffsetTableSize) {
+    return false;
+  }
+  uint16_t numTables = ReadBigEndian16(aData + offsetTable + 4);
+  uint32_t headerSize = kOffsetTableSize + kTableRecordSize * numTables;
+  if (aSize - offsetTable < headerSize) {
+    return false;
+  }
+
+  aFace.assign(aData + offsetTable, aData + offsetTable + headerSize);
+  for (uint16_t i = 0; i < numTables; i++) {
+    uint8_t *record = &aFace[kOffsetTableSize + kTableRecordSize * i];
+    uint32_t tableOffset = ReadBigEndian32(record + 8);
+    uint32_t tableLength = ReadBigEndian32(record + 12);
+    if (tableOffset > aSize || aSize - tableOffset < tableLength) {
+      return false;
+    }
+
+    // Tables are 4-byte aligned in the new file as well.
+    while (aFace.size() % 4) {
+      aFace.push_back(0);
+    }
+    // aFace may have been reallocated, so look the record up again.
+    WriteBigEndian32(&aFace[kOffsetTableSize + kTableRecordSize * i] + 8, aFace.size());
+    aFace.insert(aFace.end(), aData + tableOffset, aData + tableOffset + tableLength);
+  }
+  return true;
+}
+
+struct FontDataReleaser
+{
+  FontDataReleaseFunc mRelease;
+  void *mClosure;
+};
+
+static void
+ReleaseFontData(const void *aData, size_t aSize, void *aContext)
+{
+  FontDataReleaser *releaser = static_cast<FontDataReleaser*>(aContext);
+  releaser->mRelease(releaser->mClosure);
+  delete releaser;
+}
+
+static void
+DeleteFontVector(const void *aData, size_t aSize, void *aContext)
+{
+  delete static_cast<std::vector<uint8_t>*>(aContext);
+}
+
+/* Wraps the caller's font data. With aRelease, the SkData reads aData in place and calls
+ * aRelease(aClosure) when its last reference goes away; otherwise it holds a copy. */
+static SkData*
+WrapFontData(uint8_t *aData, uint32_t aSize, FontDataReleaseFunc aRelease, void *aClosure)
+{
+  if (!aRelease) {
+    return SkData::NewWithCopy(aData, aSize);
+  }
+  FontDataReleaser *releaser = new FontDataReleaser;
+  releaser->mRelease = aRelease;
+  releaser->mClosure = aClosure;
+  return SkData::NewWithProc(aData, aSize, ReleaseFontData, releaser);
+}
+
+/* Hands aData, which must be heap allocated, over to an SkData without copying it. */
+static SkData*
+AdoptFontVector(std::vector<uint8_t> *aData)
+{
+  return SkData::NewWithProc(&aData->front(), aData->size(), DeleteFontVector, aData);
+}
+
+/* Creates a typeface for the face at aFaceIndex in aData. A single sfnt is read in place,
+ * holding a reference to aData, since Skia's caches can keep the typeface alive after the
+ * font that made it is gone. A face in a collection is copied out once. */
+static SkTypeface*
+CreateTypefaceFromData(SkData *aData, uint32_t aFaceIndex)
+{
+  std::vector<uint8_t> *face = new std::vector<uint8_t>();
+  SkStream *stream;
+  if (ExtractCollectionFace(aData->bytes(), aData->size(), aFaceIndex, *face)) {
+    SkData *faceData = AdoptFontVector(face);
+    stream = new SkMemoryStream(faceData);
+    faceData->unref();
+  } else {
+    delete face;
+    if (aFaceIndex != 0) {
+      gfxWarning() << "Font data has no face at index " << aFaceIndex;
+      return nullptr;
+    }
+    stream = new SkMemoryStream(aData);
+  }
+  return SkTypeface::CreateFromStream(stream);
+}
+
+static bool
+ReadFontFile(const std::string &aFile, std::vector<uint8_t> &aData)
+{
+  FILE *file = fopen(aFile.c_str(), "rb");
+  if (!file) {
+    gfxWarning() << "Unable to open font file " << aFile;
+    return false;
+  }
+  uint8_t buffer[4096];
+  size_t read;
+  while ((read = fread(buffer, 1, sizeof(buffer), file)) > 0) {
+    aData.insert(aData.end(), buffer, buffer + read);
+  }
+  fclose(file);
+
+  if (aData.empty()) {
+    gfxWarning() << "Font file " << aFile << " is empty";
+    return false;
+  }
+  return true;
+}
+
+/* A font file loaded for a face, shared by every font of that face so that the file is only
+ * read once. The entry is dropped once no font uses it. */
+struct FontFile
+{
+  SkData *mData;
+  SkTypeface *mTypeface;
+  uint32_t mUsers;
+};
+
+typedef std::map<std::pair<std::string, uint32_t>, FontFile> FontFileMap;
+
+// Fonts may be created and destroyed on different threads.
+static Mutex sFontFilesLock("ScaledFontFreetype font files");
+static FontFileMap sFontFiles;
+
+/* Returns the entry for the face at aFaceIndex in aFile, reading the file if no font uses it
+ * yet, or null if it can't be read. Each call must be paired with ReleaseFontFile. */
+static FontFile*
+AcquireFontFile(const std::string &aFile, uint32_t aFaceIndex)
+{
+  MutexAutoLock lock(sFontFilesLock);
+  FontFileMap::key_type key(aFile, aFaceIndex);
+  FontFileMap::iterator entry = sFontFiles.find(key);
+  if (entry == sFontFiles.end()) {
+    std::vector<uint8_t> *data = new std::vector<uint8_t>();
+    if (!ReadFontFile(aFile, *data)) {
+      delete data;
+      return nullptr;
+    }
+    FontFile file;
+    file.mData = AdoptFontVector(data);
+    file.mTypeface = CreateTypefaceFromData(file.mData, aFaceIndex);
+    file.mUsers = 0;
+    entry = sFontFiles.insert(FontFileMap::value_type(key, file)).first;
+  }
+  entry->second.mUsers++;
+  return &entry->second;
+}
+
+static void
+ReleaseFontFile(const std::string &aFile, uint32_t aFaceIndex)
+{
+  MutexAutoLock lock(sFontFilesLock);
+  FontFileMap::iterator entry = sFontFiles.find(FontFileMap::key_type(aFile, aFaceIndex));
+  if (--entry->second.mUsers == 0) {
+    SkSafeUnref(entry->second.mTypeface);
+    entry->second.mData->unref();
+    sFontFiles.erase(entry);
+  }
+}
+#endif
+
+#ifdef MOZ_ENABLE_FREETYPE
+static FT_Library
+GetFreetypeLibrary()
+{
+  static FT_Library sLibrary = nullptr;
+  if (!sLibrary && FT_Init_FreeType(&sLibrary) != 0) {
+    gfxWarning() << "Unable to initialize FreeType";
+    sLibrary = nullptr;
+  }
+  return sLibrary;
+}
+
+void
+ScaledFontFreetype::InitColorFace(SkData *aData, uint32_t aFaceIndex)
+{
+  FT_Library library = GetFreetypeLibrary();
+  if (!library ||
+      FT_New_Memory_Face(library, aData->bytes(), aData->size(), aFaceIndex, &mColorFace) != 0) {
+    mColorFace = nullptr;
+    return;
+  }
+  if (!FT_HAS_COLOR(mColorFace) || mColorFace->num_fixed_sizes == 0) {
+    FT_Done_Face(mColorFace);
+    mColorFace = nullptr;
+    return;
+  }
+
+  // Use the smallest strike at least as large as the font, or else the largest one, and
+  // scale its bitmaps to the font size when drawing.
+  FT_Pos size = FT_Pos(mSize * 64);
+  int strike = 0;
+  for (int i = 1; i < mColorFace->num_fixed_sizes; i++) {
+    FT_Pos ppem = mColorFace->available_sizes[i].y_ppem;
+    FT_Pos best = mColorFace->available_sizes[strike].y_ppem;
+    if ((best < size && ppem > best) || (ppem >= size && ppem < best)) {
+      strike = i;
+    }
+  }
+  if (FT_Select_Size(mColorFace, strike) != 0) {
+    FT_Done_Face(mColorFace);
+    mColorFace = nullptr;
+    return;
+  }
+
+  // The face reads the same data as the typeface, so keep it alive alongside.
+  mColorFaceData = SkRef(aData);
+}
+
+
+bool
+ScaledFontFreetype::GetColorGlyph(uint32_t aGlyph, SkBitmap &aBitmap, Rect &aBounds)
+{
+  if (!mColorFace || FT_Load_Glyph(mColorFace, aGlyph, FT_LOAD_COLOR) != 0) {
+    return false;
+  }
+
+  FT_GlyphSlot slot = mColorFace->glyph;
+  const FT_Bitmap &bitmap = slot->bitmap;
+  if (slot->format != FT_GLYPH_FORMAT_BITMAP || bitmap.pixel_mode != FT_PIXEL_MODE_BGRA ||
+      bitmap.width == 0 || bitmap.rows == 0) {
+    return false;
+  }
+
+  // FreeType's BGRA bitmaps are premultiplied, like Skia's 32-bit bitmaps.
+  aBitmap.setConfig(SkBitmap::kARGB_8888_Config, bitmap.width, bitmap.rows);
+  if (!aBitmap.allocPixels()) {
+    return false;
+  }
+  for (unsigned int y = 0; y < bitmap.rows; y++) {
+    memcpy(aBitmap.getAddr32(0, y), bitmap.buffer + y * bitmap.pitch, bitmap.width * 4);
+  }
+
+  Float scale = mSize / mColorFace->size->metrics.y_ppem;
+  aBounds = Rect(slot->bitmap_left * scale, -slot->bitmap_top * scale,
+                 bitmap.width * scale, bitmap.rows * scale);
+  return true;
+}
+#endif
+
+ScaledFontFreetype::ScaledFontFreetype(uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex,
+                                       Float aGlyphSize, FontDataReleaseFunc aRelease,
+                                       void *aClosure)
+  : ScaledFontBase(aGlyphSize)
+  , mFaceIndex(aFaceIndex)
+{
+#ifdef MOZ_ENABLE_FREETYPE
+  mColorFace = nullptr;
+  mColorFaceData = nullptr;
+#endif
+#ifdef USE_SKIA
+  SkData *data = WrapFontData(aData, aSize, aRelease, aClosure);
+  mTypeface = CreateTypefaceFromData(data, aFaceIndex);
+#ifdef MOZ_ENABLE_FREETYPE
+  InitColorFace(data, aFaceIndex);
+#endif
+  data->unref();
+#else
+  if (aRelease) {
+    aRelease(aClosure);
+  }
+#endif
+}
+
 ScaledFontFreetype::ScaledFontFreetype(FontOptions* aFont, Float aSize)
   : ScaledFontBase(aSize)
+  , mFaceIndex(aFont->mFaceIndex)
 {
+#ifdef MOZ_ENABLE_FREETYPE
+  mColorFace = nullptr;
+  mColorFaceData = nullptr;
+#endif
 #ifdef USE_SKIA
-  if (aFont->mData)
+  if (!aFont->mFile.empty())
   {
-    SkStream *stream = new SkMemoryStream(aFont->mData, aFont->mDataSize, true);
-    mTypeface = SkTypeface::CreateFromStream(stream);
+    FontFile *file = AcquireFontFile(aFont->mFile, aFont->mFaceIndex);
+    if (file) {
+      mFile = aFont->mFile;
+      mTypeface = SkSafeRef(file->mTypeface);
+#ifdef MOZ_ENABLE_FREETYPE
+      InitColorFace(file->mData, aFont->mFaceIndex);
+#endif
+    }
+  }
+  else if (aFont->mData)
+  {
+    SkData *data = WrapFontData(aFont->mData, aFont->mDataSize, nullptr, nullptr);
+    mTypeface = CreateTypefaceFromData(data, aFont->mFaceIndex);
+#ifdef MOZ_ENABLE_FREETYPE
+    InitColorFace(data, aFont->mFaceIndex);
+#endif
+    data->unref();
   }
   else
   {
@@ -54,5 +393,20 @@ ScaledFontFreetype::ScaledFontFreetype(FontOptions* aFont, Float aSize)
 #endif
 }
 
+ScaledFontFreetype::~ScaledFontFreetype()
+{
+#ifdef MOZ_ENABLE_FREETYPE
+  if (mColorFace) {
+    FT_Done_Face(mColorFace);
+  }
+  SkSafeUnref(mColorFaceData);
+#endif
+#ifdef USE_SKIA
+  if (!mFile.empty()) {
+    ReleaseFontFile(mFile, mFaceIndex);
+  }
+#endif
+}
+
 }
 }
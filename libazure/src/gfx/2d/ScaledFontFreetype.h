@@ -8,6 +8,15 @@
 
 #include "ScaledFontBase.h"
 
+#include <string>
+
+#ifdef MOZ_ENABLE_FREETYPE
+#include <ft2build.h>
+#include FT_FREETYPE_H
+#endif
+
+class SkData;
+
 namespace mozilla {
 namespace gfx {
 
@@ -16,6 +25,36 @@ class ScaledFontFreetype : public ScaledFontBase
 public:
 
   ScaledFontFreetype(FontOptions* aFont, Float aSize);
+
+  /* Creates a font from the face at aFaceIndex in aData, which may be a single sfnt or a
+   * TrueType collection. With aRelease, the font reads aData in place and calls
+   * aRelease(aClosure) once it is done with it; otherwise it copies the data. Check
+   * GetSkTypeface() for failure. */
+  ScaledFontFreetype(uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex, Float aGlyphSize,
+                     FontDataReleaseFunc aRelease, void *aClosure);
+
+  virtual ~ScaledFontFreetype();
+
+#ifdef MOZ_ENABLE_FREETYPE
+  virtual bool GetColorGlyph(uint32_t aGlyph, SkBitmap &aBitmap, Rect &aBounds);
+#endif
+
+private:
+  // The font file this font shares with other fonts of the same face, if it was loaded from
+  // one, and the face in it.
+  std::string mFile;
+  uint32_t mFaceIndex;
+
+#ifdef MOZ_ENABLE_FREETYPE
+  /* Opens a FreeType face on the font data if it has color bitmap strikes, and selects the
+   * strike closest to the font size. */
+  void InitColorFace(SkData *aData, uint32_t aFaceIndex);
+
+  // Null unless the face has color bitmap glyphs, in which case it reads mColorFaceData, the
+  // data the typeface was created from.
+  FT_Face mColorFace;
+  SkData *mColorFaceData;
+#endif
 };
 
 }
@@ -10,6 +10,7 @@
 #include "SkDevice.h"
 #include "HelpersSkia.h"
 #include "DrawTargetSkia.h"
+#include "Tools.h"
 
 namespace mozilla {
 namespace gfx {
@@ -42,6 +43,24 @@ SourceSurfaceSkia::InitFromData(unsigned char* aData,
                                 int32_t aStride,
                                 SurfaceFormat aFormat)
 {
+  if (ToBGRAFormat(aFormat) != aFormat) {
+    mBitmap.setConfig(SkBitmap::kARGB_8888_Config, aSize.width, aSize.height);
+    if (!mBitmap.allocPixels()) {
+      return false;
+    }
+    mBitmap.lockPixels();
+    ConvertToBGRA(aData, aStride, aFormat, reinterpret_cast<uint8_t*>(mBitmap.getPixels()),
+                  mBitmap.rowBytes(), aSize);
+    mBitmap.unlockPixels();
+    mBitmap.notifyPixelsChanged();
+    mBitmap.setIsOpaque(aFormat == FORMAT_R8G8B8X8);
+
+    mSize = aSize;
+    mFormat = ToBGRAFormat(aFormat);
+    mStride = mBitmap.rowBytes();
+    return true;
+  }
+
   SkBitmap temp;
   temp.setConfig(GfxFormatToSkiaConfig(aFormat), aSize.width, aSize.height, aStride);
   temp.setPixels(aData);
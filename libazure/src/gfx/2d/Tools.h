@@ -76,11 +76,63 @@ BytesPerPixel(SurfaceFormat aFormat)
     return 1;
   case FORMAT_R5G6B5:
     return 2;
+  case FORMAT_R16G16B16A16:
+    return 8;
   default:
     return 4;
   }
 }
 
+/**
+ * Returns the format that backends convert surfaces in aFormat to, which is
+ * aFormat itself for the formats every backend understands. Skia and cairo
+ * only understand BGRA among the 32-bit formats, and neither has wide ones, so
+ * RGBA and R16G16B16A16 data is converted with ConvertToBGRA when a surface is
+ * created from it, and their pixel formats map these to BGRA.
+ */
+static inline SurfaceFormat
+ToBGRAFormat(SurfaceFormat aFormat)
+{
+  switch (aFormat) {
+  case FORMAT_R8G8B8A8:
+  case FORMAT_R16G16B16A16:
+    return FORMAT_B8G8R8A8;
+  case FORMAT_R8G8B8X8:
+    return FORMAT_B8G8R8X8;
+  default:
+    return aFormat;
+  }
+}
+
+/**
+ * Converts aSize pixels in aFormat to ToBGRAFormat(aFormat), which must differ
+ * from aFormat. The X channel is filled with 0xFF.
+ */
+static inline void
+ConvertToBGRA(const uint8_t *aSource, int32_t aSourceStride, SurfaceFormat aFormat,
+              uint8_t *aDest, int32_t aDestStride, const IntSize &aSize)
+{
+  for (int32_t y = 0; y < aSize.height; y++) {
+    const uint8_t *source = aSource + y * aSourceStride;
+    uint8_t *dest = aDest + y * aDestStride;
+    for (int32_t x = 0; x < aSize.width; x++, dest += 4) {
+      if (aFormat == FORMAT_R16G16B16A16) {
+        const uint16_t *pixel = reinterpret_cast<const uint16_t*>(source) + x * 4;
+        for (int32_t i = 0; i < 3; i++) {
+          dest[i] = (pixel[2 - i] * 255 + 32767) / 65535;
+        }
+        dest[3] = (pixel[3] * 255 + 32767) / 65535;
+      } else {
+        const uint8_t *pixel = source + x * 4;
+        dest[0] = pixel[2];
+        dest[1] = pixel[1];
+        dest[2] = pixel[0];
+        dest[3] = aFormat == FORMAT_R8G8B8X8 ? 0xFF : pixel[3];
+      }
+    }
+  }
+}
+
 template<typename T, int alignment = 16>
 struct AlignedArray
 {
@@ -9,6 +9,9 @@
 #ifdef WIN32
 #include "TestDrawTargetD2D.h"
 #endif
+#ifdef USE_SKIA
+#include "TestDrawTargetSkia.h"
+#endif
 
 #include <string>
 #include <sstream>
@@ -29,6 +32,9 @@ main()
     { new SanityChecks(), "Sanity Checks" },
   #ifdef WIN32
     { new TestDrawTargetD2D(), "DrawTarget (D2D)" },
+  #endif
+  #ifdef USE_SKIA
+    { new TestDrawTargetSkia(), "DrawTarget (Skia)" },
   #endif
     { new TestPoint(), "Point Tests" },
     { new TestScaling(), "Scaling Tests" }
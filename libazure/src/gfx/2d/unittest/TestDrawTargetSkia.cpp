@@ -0,0 +1,12 @@
+/* -*- Mode: C++; tab-width: 20; indent-tabs-mode: nil; c-basic-offset: 2 -*-
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+#include "TestDrawTargetSkia.h"
+
+using namespace mozilla::gfx;
+TestDrawTargetSkia::TestDrawTargetSkia()
+{
+  mDT = Factory::CreateDrawTarget(BACKEND_SKIA, IntSize(DT_WIDTH, DT_HEIGHT), FORMAT_B8G8R8A8);
+}
@@ -4,9 +4,36 @@
 
 #include "azure-c.h"
 #include "mozilla/gfx/2D.h"
+#include "mozilla/gfx/Blur.h"
+#include "BoxShadow.h"
+#include "DrawTargetDual.h"
+#include "ImageScaling.h"
+#include "ScaledFontBase.h"
+
+#ifdef USE_SKIA
+#include "SkBitmap.h"
+#include "SkColorPriv.h"
+#include "image_operations.h"
+#endif
+
+#ifdef USE_CAIRO
+#include "cairo.h"
+#include "cairo-ft.h"
+#endif
+
+#ifdef MOZ_ENABLE_FREETYPE
+#include <ft2build.h>
+#include FT_FREETYPE_H
+#endif
+
+#ifdef MOZ_ENABLE_FONTCONFIG
+#include <fontconfig/fontconfig.h>
+#endif
 
 #include <assert.h>
+#include <stddef.h>
 #include <string.h>
+#include <vector>
 
 using namespace mozilla;
 
@@ -18,6 +45,18 @@ static AzIntSize IntSizeToC(gfx::IntSize src) {
     return dst;
 }
 
+static AzRect RectToC(gfx::Rect src) {
+    AzRect dst;
+    memcpy(&dst, &src, sizeof(dst));
+    return dst;
+}
+
+static AzMatrix MatrixToC(gfx::Matrix src) {
+    AzMatrix dst;
+    memcpy(&dst, &src, sizeof(dst));
+    return dst;
+}
+
 
 #define CHECK_SIZE(name) assert(sizeof(Az##name) == sizeof(gfx::name))
 #define CHECK_ENUM(name) assert((int)AZ_##name == (int)gfx::name)
@@ -40,6 +79,8 @@ void AzSanityCheck() {
     CHECK_SIZE(Glyph);
     CHECK_SIZE(GlyphBuffer);
     CHECK_SIZE(NativeFont);
+    CHECK_SIZE(FontMetrics);
+    CHECK_SIZE(RectCornerRadii);
 
     CHECK_ENUM(SURFACE_DATA);
     CHECK_ENUM(SURFACE_D2D1_BITMAP);
@@ -55,12 +96,17 @@ void AzSanityCheck() {
     CHECK_ENUM(FORMAT_B8G8R8X8);
     CHECK_ENUM(FORMAT_R5G6B5);
     CHECK_ENUM(FORMAT_A8);
+    CHECK_ENUM(FORMAT_R8G8B8A8);
+    CHECK_ENUM(FORMAT_R8G8B8X8);
+    CHECK_ENUM(FORMAT_R16G16B16A16);
 
     CHECK_ENUM(BACKEND_NONE);
     CHECK_ENUM(BACKEND_DIRECT2D);
     CHECK_ENUM(BACKEND_COREGRAPHICS);
+    CHECK_ENUM(BACKEND_COREGRAPHICS_ACCELERATED);
     CHECK_ENUM(BACKEND_CAIRO);
     CHECK_ENUM(BACKEND_SKIA);
+    CHECK_ENUM(BACKEND_RECORDING);
 
     CHECK_ENUM(FONT_DWRITE);
     CHECK_ENUM(FONT_GDI);
@@ -69,6 +115,11 @@ void AzSanityCheck() {
     CHECK_ENUM(FONT_CAIRO);
     CHECK_ENUM(FONT_COREGRAPHICS);
 
+    CHECK_ENUM(FONT_HINTING_NONE);
+    CHECK_ENUM(FONT_HINTING_LIGHT);
+    CHECK_ENUM(FONT_HINTING_NORMAL);
+    CHECK_ENUM(FONT_HINTING_FULL);
+
     CHECK_ENUM(NATIVE_SURFACE_D3D10_TEXTURE);
     CHECK_ENUM(NATIVE_SURFACE_CAIRO_SURFACE);
     CHECK_ENUM(NATIVE_SURFACE_CGCONTEXT);
@@ -107,6 +158,7 @@ void AzSanityCheck() {
     CHECK_ENUM(AA_NONE);
     CHECK_ENUM(AA_GRAY);
     CHECK_ENUM(AA_SUBPIXEL);
+    CHECK_ENUM(AA_DEFAULT);
 
     CHECK_ENUM(SNAP_NONE);
     CHECK_ENUM(SNAP_ALIGNED);
@@ -137,6 +189,222 @@ void AzSanityCheck() {
     assert((int)AZ_eSideLeft == (int)css::eSideLeft);
 }
 
+#define LAYOUT_SIZE(type) { #type, sizeof(type) }
+#define LAYOUT_OFFSET(type, field) { #type "." #field, offsetof(type, field) }
+#define LAYOUT_ENUM(name) { #name, name }
+
+static const AzLayoutEntry sLayoutEntries[] = {
+    LAYOUT_SIZE(AzColor),
+    LAYOUT_OFFSET(AzColor, r),
+    LAYOUT_OFFSET(AzColor, g),
+    LAYOUT_OFFSET(AzColor, b),
+    LAYOUT_OFFSET(AzColor, a),
+    LAYOUT_SIZE(AzGradientStop),
+    LAYOUT_OFFSET(AzGradientStop, offset),
+    LAYOUT_OFFSET(AzGradientStop, color),
+    LAYOUT_SIZE(AzIntRect),
+    LAYOUT_OFFSET(AzIntRect, x),
+    LAYOUT_OFFSET(AzIntRect, y),
+    LAYOUT_OFFSET(AzIntRect, width),
+    LAYOUT_OFFSET(AzIntRect, height),
+    LAYOUT_SIZE(AzRect),
+    LAYOUT_OFFSET(AzRect, x),
+    LAYOUT_OFFSET(AzRect, y),
+    LAYOUT_OFFSET(AzRect, width),
+    LAYOUT_OFFSET(AzRect, height),
+    LAYOUT_SIZE(AzIntPoint),
+    LAYOUT_OFFSET(AzIntPoint, x),
+    LAYOUT_OFFSET(AzIntPoint, y),
+    LAYOUT_SIZE(AzPoint),
+    LAYOUT_OFFSET(AzPoint, x),
+    LAYOUT_OFFSET(AzPoint, y),
+    LAYOUT_SIZE(AzIntSize),
+    LAYOUT_OFFSET(AzIntSize, width),
+    LAYOUT_OFFSET(AzIntSize, height),
+    LAYOUT_SIZE(AzSize),
+    LAYOUT_OFFSET(AzSize, width),
+    LAYOUT_OFFSET(AzSize, height),
+    LAYOUT_SIZE(AzMatrix),
+    LAYOUT_OFFSET(AzMatrix, _11),
+    LAYOUT_OFFSET(AzMatrix, _12),
+    LAYOUT_OFFSET(AzMatrix, _21),
+    LAYOUT_OFFSET(AzMatrix, _22),
+    LAYOUT_OFFSET(AzMatrix, _31),
+    LAYOUT_OFFSET(AzMatrix, _32),
+    LAYOUT_SIZE(AzDrawOptions),
+    LAYOUT_OFFSET(AzDrawOptions, mAlpha),
+    LAYOUT_OFFSET(AzDrawOptions, fields),
+    LAYOUT_SIZE(AzStrokeOptions),
+    LAYOUT_OFFSET(AzStrokeOptions, mLineWidth),
+    LAYOUT_OFFSET(AzStrokeOptions, mMiterLimit),
+    LAYOUT_OFFSET(AzStrokeOptions, mDashPattern),
+    LAYOUT_OFFSET(AzStrokeOptions, mDashLength),
+    LAYOUT_OFFSET(AzStrokeOptions, mDashOffset),
+    LAYOUT_OFFSET(AzStrokeOptions, fields),
+    LAYOUT_SIZE(AzDrawSurfaceOptions),
+    LAYOUT_OFFSET(AzDrawSurfaceOptions, fields),
+    LAYOUT_SIZE(AzGlyph),
+    LAYOUT_OFFSET(AzGlyph, mIndex),
+    LAYOUT_OFFSET(AzGlyph, mPosition),
+    LAYOUT_SIZE(AzGlyphBuffer),
+    LAYOUT_OFFSET(AzGlyphBuffer, mGlyphs),
+    LAYOUT_OFFSET(AzGlyphBuffer, mNumGlyphs),
+    LAYOUT_SIZE(AzNativeFont),
+    LAYOUT_OFFSET(AzNativeFont, mType),
+    LAYOUT_OFFSET(AzNativeFont, mFont),
+    LAYOUT_SIZE(AzFontMetrics),
+    LAYOUT_OFFSET(AzFontMetrics, mAscent),
+    LAYOUT_OFFSET(AzFontMetrics, mDescent),
+    LAYOUT_OFFSET(AzFontMetrics, mLeading),
+    LAYOUT_OFFSET(AzFontMetrics, mXHeight),
+    LAYOUT_OFFSET(AzFontMetrics, mUnderlinePosition),
+    LAYOUT_OFFSET(AzFontMetrics, mUnderlineThickness),
+    LAYOUT_SIZE(AzRectCornerRadii),
+    LAYOUT_OFFSET(AzRectCornerRadii, mTopLeft),
+    LAYOUT_OFFSET(AzRectCornerRadii, mTopRight),
+    LAYOUT_OFFSET(AzRectCornerRadii, mBottomRight),
+    LAYOUT_OFFSET(AzRectCornerRadii, mBottomLeft),
+
+    LAYOUT_ENUM(AZ_SURFACE_DATA),
+    LAYOUT_ENUM(AZ_SURFACE_D2D1_BITMAP),
+    LAYOUT_ENUM(AZ_SURFACE_D2D1_DRAWTARGET),
+    LAYOUT_ENUM(AZ_SURFACE_CAIRO),
+    LAYOUT_ENUM(AZ_SURFACE_CAIRO_IMAGE),
+    LAYOUT_ENUM(AZ_SURFACE_COREGRAPHICS_IMAGE),
+    LAYOUT_ENUM(AZ_SURFACE_COREGRAPHICS_CGCONTEXT),
+    LAYOUT_ENUM(AZ_SURFACE_SKIA),
+    LAYOUT_ENUM(AZ_SURFACE_DUAL_DT),
+
+    LAYOUT_ENUM(AZ_FORMAT_B8G8R8A8),
+    LAYOUT_ENUM(AZ_FORMAT_B8G8R8X8),
+    LAYOUT_ENUM(AZ_FORMAT_R5G6B5),
+    LAYOUT_ENUM(AZ_FORMAT_A8),
+    LAYOUT_ENUM(AZ_FORMAT_R8G8B8A8),
+    LAYOUT_ENUM(AZ_FORMAT_R8G8B8X8),
+    LAYOUT_ENUM(AZ_FORMAT_R16G16B16A16),
+
+    LAYOUT_ENUM(AZ_BACKEND_NONE),
+    LAYOUT_ENUM(AZ_BACKEND_DIRECT2D),
+    LAYOUT_ENUM(AZ_BACKEND_COREGRAPHICS),
+    LAYOUT_ENUM(AZ_BACKEND_COREGRAPHICS_ACCELERATED),
+    LAYOUT_ENUM(AZ_BACKEND_CAIRO),
+    LAYOUT_ENUM(AZ_BACKEND_SKIA),
+    LAYOUT_ENUM(AZ_BACKEND_RECORDING),
+
+    LAYOUT_ENUM(AZ_FONT_DWRITE),
+    LAYOUT_ENUM(AZ_FONT_GDI),
+    LAYOUT_ENUM(AZ_FONT_MAC),
+    LAYOUT_ENUM(AZ_FONT_SKIA),
+    LAYOUT_ENUM(AZ_FONT_CAIRO),
+    LAYOUT_ENUM(AZ_FONT_COREGRAPHICS),
+
+    LAYOUT_ENUM(AZ_NATIVE_SURFACE_D3D10_TEXTURE),
+    LAYOUT_ENUM(AZ_NATIVE_SURFACE_CAIRO_SURFACE),
+    LAYOUT_ENUM(AZ_NATIVE_SURFACE_CGCONTEXT),
+
+    LAYOUT_ENUM(AZ_NATIVE_FONT_DWRITE_FONT_FACE),
+    LAYOUT_ENUM(AZ_NATIVE_FONT_GDI_FONT_FACE),
+    LAYOUT_ENUM(AZ_NATIVE_FONT_MAC_FONT_FACE),
+    LAYOUT_ENUM(AZ_NATIVE_FONT_SKIA_FONT_FACE),
+    LAYOUT_ENUM(AZ_NATIVE_FONT_CAIRO_FONT_FACE),
+
+    LAYOUT_ENUM(AZ_FONT_STYLE_NORMAL),
+    LAYOUT_ENUM(AZ_FONT_STYLE_ITALIC),
+    LAYOUT_ENUM(AZ_FONT_STYLE_BOLD),
+    LAYOUT_ENUM(AZ_FONT_STYLE_BOLD_ITALIC),
+
+    LAYOUT_ENUM(AZ_FONT_HINTING_NONE),
+    LAYOUT_ENUM(AZ_FONT_HINTING_LIGHT),
+    LAYOUT_ENUM(AZ_FONT_HINTING_NORMAL),
+    LAYOUT_ENUM(AZ_FONT_HINTING_FULL),
+
+    LAYOUT_ENUM(AZ_FONT_SLANT_NORMAL),
+    LAYOUT_ENUM(AZ_FONT_SLANT_ITALIC),
+    LAYOUT_ENUM(AZ_FONT_SLANT_OBLIQUE),
+
+    LAYOUT_ENUM(AZ_RESIZE_GOOD),
+    LAYOUT_ENUM(AZ_RESIZE_BETTER),
+    LAYOUT_ENUM(AZ_RESIZE_BEST),
+    LAYOUT_ENUM(AZ_RESIZE_BOX),
+    LAYOUT_ENUM(AZ_RESIZE_HAMMING1),
+    LAYOUT_ENUM(AZ_RESIZE_LANCZOS2),
+    LAYOUT_ENUM(AZ_RESIZE_LANCZOS3),
+
+    LAYOUT_ENUM(AZ_OP_OVER),
+    LAYOUT_ENUM(AZ_OP_ADD),
+    LAYOUT_ENUM(AZ_OP_ATOP),
+    LAYOUT_ENUM(AZ_OP_OUT),
+    LAYOUT_ENUM(AZ_OP_IN),
+    LAYOUT_ENUM(AZ_OP_SOURCE),
+    LAYOUT_ENUM(AZ_OP_DEST_IN),
+    LAYOUT_ENUM(AZ_OP_DEST_OUT),
+    LAYOUT_ENUM(AZ_OP_DEST_OVER),
+    LAYOUT_ENUM(AZ_OP_DEST_ATOP),
+    LAYOUT_ENUM(AZ_OP_XOR),
+    LAYOUT_ENUM(AZ_OP_MULTIPLY),
+    LAYOUT_ENUM(AZ_OP_SCREEN),
+    LAYOUT_ENUM(AZ_OP_OVERLAY),
+    LAYOUT_ENUM(AZ_OP_DARKEN),
+    LAYOUT_ENUM(AZ_OP_LIGHTEN),
+    LAYOUT_ENUM(AZ_OP_COLOR_DODGE),
+    LAYOUT_ENUM(AZ_OP_COLOR_BURN),
+    LAYOUT_ENUM(AZ_OP_HARD_LIGHT),
+    LAYOUT_ENUM(AZ_OP_SOFT_LIGHT),
+    LAYOUT_ENUM(AZ_OP_DIFFERENCE),
+    LAYOUT_ENUM(AZ_OP_EXCLUSION),
+    LAYOUT_ENUM(AZ_OP_HUE),
+    LAYOUT_ENUM(AZ_OP_SATURATION),
+    LAYOUT_ENUM(AZ_OP_COLOR),
+    LAYOUT_ENUM(AZ_OP_LUMINOSITY),
+    LAYOUT_ENUM(AZ_OP_COUNT),
+
+    LAYOUT_ENUM(AZ_EXTEND_CLAMP),
+    LAYOUT_ENUM(AZ_EXTEND_REPEAT),
+    LAYOUT_ENUM(AZ_EXTEND_REFLECT),
+
+    LAYOUT_ENUM(AZ_FILL_WINDING),
+    LAYOUT_ENUM(AZ_FILL_EVEN_ODD),
+
+    LAYOUT_ENUM(AZ_AA_NONE),
+    LAYOUT_ENUM(AZ_AA_GRAY),
+    LAYOUT_ENUM(AZ_AA_SUBPIXEL),
+    LAYOUT_ENUM(AZ_AA_DEFAULT),
+
+    LAYOUT_ENUM(AZ_SNAP_NONE),
+    LAYOUT_ENUM(AZ_SNAP_ALIGNED),
+
+    LAYOUT_ENUM(AZ_FILTER_LINEAR),
+    LAYOUT_ENUM(AZ_FILTER_POINT),
+
+    LAYOUT_ENUM(AZ_PATTERN_COLOR),
+    LAYOUT_ENUM(AZ_PATTERN_SURFACE),
+    LAYOUT_ENUM(AZ_PATTERN_LINEAR_GRADIENT),
+    LAYOUT_ENUM(AZ_PATTERN_RADIAL_GRADIENT),
+
+    LAYOUT_ENUM(AZ_JOIN_BEVEL),
+    LAYOUT_ENUM(AZ_JOIN_ROUND),
+    LAYOUT_ENUM(AZ_JOIN_MITER),
+    LAYOUT_ENUM(AZ_JOIN_MITER_OR_BEVEL),
+
+    LAYOUT_ENUM(AZ_CAP_BUTT),
+    LAYOUT_ENUM(AZ_CAP_ROUND),
+    LAYOUT_ENUM(AZ_CAP_SQUARE),
+
+    LAYOUT_ENUM(AZ_SAMPLING_UNBOUNDED),
+    LAYOUT_ENUM(AZ_SAMPLING_BOUNDED),
+
+    LAYOUT_ENUM(AZ_eSideTop),
+    LAYOUT_ENUM(AZ_eSideRight),
+    LAYOUT_ENUM(AZ_eSideBottom),
+    LAYOUT_ENUM(AZ_eSideLeft),
+};
+
+extern "C" const AzLayoutEntry *
+AzGetLayoutEntries(uint32_t *aCount) {
+    *aCount = sizeof(sLayoutEntries) / sizeof(sLayoutEntries[0]);
+    return sLayoutEntries;
+}
+
 
 extern "C" AzColorPatternRef
 AzCreateColorPattern(AzColor *aColor) {
@@ -200,6 +468,30 @@ AzSkiaSharedGLContextFlush(AzSkiaSharedGLContextRef aGLContext) {
     sharedGLContext->flush();
 }
 
+extern "C" bool
+AzCPUSupportsSSE2() {
+    return gfx::Factory::CPUSupportsSSE2();
+}
+
+extern "C" bool
+AzSSE2PathsCompiled() {
+#ifdef USE_SSE2
+    return true;
+#else
+    return false;
+#endif
+}
+
+extern "C" bool
+AzHasSSE2() {
+    return AzSSE2PathsCompiled() && gfx::Factory::HasSSE2();
+}
+
+extern "C" void
+AzSetSSE2Disabled(bool aDisabled) {
+    gfx::Factory::SetSSE2Disabled(aDisabled);
+}
+
 extern "C" AzDrawTargetRef
 AzCreateDrawTarget(AzBackendType aBackend, AzIntSize *aSize, AzSurfaceFormat aFormat) {
     gfx::BackendType backendType = static_cast<gfx::BackendType>(aBackend);
@@ -229,6 +521,15 @@ AzCreateDrawTargetForData(AzBackendType aBackend, unsigned char *aData, AzIntSiz
     return target;
 }
 
+extern "C" AzDrawTargetRef
+AzCreateDualDrawTarget(AzDrawTargetRef aTargetA, AzDrawTargetRef aTargetB) {
+    gfx::DrawTarget *gfxTargetA = static_cast<gfx::DrawTarget*>(aTargetA);
+    gfx::DrawTarget *gfxTargetB = static_cast<gfx::DrawTarget*>(aTargetB);
+    RefPtr<gfx::DrawTarget> target = new gfx::DrawTargetDual(gfxTargetA, gfxTargetB);
+    target->AddRef();
+    return target;
+}
+
 extern "C" AzDrawTargetRef
 AzCreateSkiaDrawTargetForFBO(AzSkiaSharedGLContextRef aGLContext, AzIntSize *aSize, AzSurfaceFormat aFormat) {
     SkNativeSharedGLContext *sharedGLContext = static_cast<SkNativeSharedGLContext*>(aGLContext);
@@ -245,6 +546,17 @@ AzCreateSkiaDrawTargetForFBO(AzSkiaSharedGLContextRef aGLContext, AzIntSize *aSi
     return target;
 }
 
+extern "C" AzDrawTargetRef
+AzCreateDrawTargetForCairoSurface(AzCairoSurfaceRef aSurface, AzIntSize *aSize) {
+    cairo_surface_t *surface = static_cast<cairo_surface_t*>(aSurface);
+    gfx::IntSize *size = reinterpret_cast<gfx::IntSize*>(aSize);
+    RefPtr<gfx::DrawTarget> target = gfx::Factory::CreateDrawTargetForCairoSurface(surface, *size);
+    if (target != NULL) {
+        target->AddRef();
+    }
+    return target;
+}
+
 extern "C" void
 AzRetainDrawTarget(AzDrawTargetRef aTarget) {
     gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aTarget);
@@ -269,6 +581,33 @@ AzDrawTargetFlush(AzDrawTargetRef aDrawTarget) {
     gfxDrawTarget->Flush();
 }
 
+extern "C" AzSurfaceFormat
+AzDrawTargetGetFormat(AzDrawTargetRef aDrawTarget) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    return static_cast<AzSurfaceFormat>(gfxDrawTarget->GetFormat());
+}
+
+extern "C" bool
+AzDrawTargetReadPixels(AzDrawTargetRef aDrawTarget,
+                       const AzIntRect *aRect,
+                       uint8_t *aData,
+                       int32_t aStride) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    const gfx::IntRect *gfxRect = reinterpret_cast<const gfx::IntRect*>(aRect);
+    return gfxDrawTarget->ReadPixels(*gfxRect, aData, aStride);
+}
+
+extern "C" AzDataSourceSurfaceRef
+AzDrawTargetSnapshotRect(AzDrawTargetRef aDrawTarget, const AzIntRect *aRect) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    const gfx::IntRect *gfxRect = reinterpret_cast<const gfx::IntRect*>(aRect);
+    RefPtr<gfx::DataSourceSurface> gfxDataSourceSurface = gfxDrawTarget->SnapshotRect(*gfxRect);
+    if (gfxDataSourceSurface != NULL) {
+        gfxDataSourceSurface->AddRef();
+    }
+    return gfxDataSourceSurface;
+}
+
 extern "C" void
 AzDrawTargetClearRect(AzDrawTargetRef aDrawTarget, AzRect *aRect) {
     gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
@@ -368,13 +707,13 @@ AzDrawTargetDrawSurface(AzDrawTargetRef aDrawTarget,
                         AzSourceSurfaceRef aSurface,
                         AzRect *aDest,
                         AzRect *aSource,
-                        AzDrawSurfaceOptionsRef aSurfOptions,
+                        AzDrawSurfaceOptions *aSurfOptions,
                         AzDrawOptions *aOptions) {
     gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
     gfx::SourceSurface *gfxSourceSurface = static_cast<gfx::SourceSurface*>(aSurface);
     gfx::Rect *gfxDest = reinterpret_cast<gfx::Rect*>(aDest);
     gfx::Rect *gfxSource = reinterpret_cast<gfx::Rect*>(aSource);
-    gfx::DrawSurfaceOptions *gfxDrawSurfaceOptions = static_cast<gfx::DrawSurfaceOptions*>(aSurfOptions);
+    gfx::DrawSurfaceOptions *gfxDrawSurfaceOptions = reinterpret_cast<gfx::DrawSurfaceOptions*>(aSurfOptions);
     gfx::DrawOptions *gfxDrawOptions = reinterpret_cast<gfx::DrawOptions*>(aOptions);
     gfxDrawTarget->DrawSurface(gfxSourceSurface, *gfxDest, *gfxSource, *gfxDrawSurfaceOptions, *gfxDrawOptions);
 }
@@ -401,6 +740,29 @@ AzDrawTargetDrawSurfaceWithShadow(AzDrawTargetRef aDrawTarget,
                                          gfxOperator);
 }
 
+extern "C" void
+AzDrawTargetDrawBoxShadow(AzDrawTargetRef aDrawTarget,
+                          const AzRect *aRect,
+                          const AzRectCornerRadii *aRadii,
+                          const AzColor *aColor,
+                          const AzPoint *aOffset,
+                          AzFloat aSpread,
+                          AzFloat aBlurSigma,
+                          bool aInset,
+                          const AzRect *aClip,
+                          AzDrawOptions *aDrawOptions) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    const gfx::Rect *gfxRect = reinterpret_cast<const gfx::Rect*>(aRect);
+    const gfx::RectCornerRadii *gfxRadii = reinterpret_cast<const gfx::RectCornerRadii*>(aRadii);
+    const gfx::Color *gfxColor = reinterpret_cast<const gfx::Color*>(aColor);
+    const gfx::Point *gfxOffset = reinterpret_cast<const gfx::Point*>(aOffset);
+    const gfx::Rect *gfxClip = reinterpret_cast<const gfx::Rect*>(aClip);
+    gfx::DrawOptions *gfxDrawOptions = reinterpret_cast<gfx::DrawOptions*>(aDrawOptions);
+    gfx::DrawBoxShadow(gfxDrawTarget, *gfxRect, *gfxRadii, *gfxColor, *gfxOffset,
+                       aSpread, aBlurSigma, aInset, gfxClip,
+                       gfxDrawOptions != NULL ? *gfxDrawOptions : gfx::DrawOptions());
+}
+
 extern "C" AzSourceSurfaceRef
 AzDrawTargetGetSnapshot(AzDrawTargetRef aDrawTarget) {
     gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
@@ -425,11 +787,12 @@ AzDrawTargetCreateSourceSurfaceFromData(AzDrawTargetRef aDrawTarget,
 
 extern "C" AzGradientStopsRef
 AzDrawTargetCreateGradientStops(AzDrawTargetRef aDrawTarget,
-                                AzGradientStop *aStops,
+                                const AzGradientStop *aStops,
                                 uint32_t aNumStops,
                                 AzExtendMode aExtendMode) {
     gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
-    gfx::GradientStop *gfxStops = reinterpret_cast<gfx::GradientStop*>(aStops);
+    gfx::GradientStop *gfxStops =
+        const_cast<gfx::GradientStop*>(reinterpret_cast<const gfx::GradientStop*>(aStops));
     gfx::ExtendMode gfxExtendMode = static_cast<gfx::ExtendMode>(aExtendMode);
     RefPtr<gfx::GradientStops> gfxGradientStops =
         gfxDrawTarget->CreateGradientStops(gfxStops, aNumStops, gfxExtendMode);
@@ -459,7 +822,10 @@ extern "C" AzDataSourceSurfaceRef
 AzSourceSurfaceGetDataSurface(AzSourceSurfaceRef aSurface) {
     gfx::SourceSurface *gfxSourceSurface = static_cast<gfx::SourceSurface*>(aSurface);
     RefPtr<gfx::DataSourceSurface> gfxDataSourceSurface = gfxSourceSurface->GetDataSurface();
-    gfxDataSourceSurface->AddRef();
+    // Some surfaces, such as snapshots of dual draw targets, don't support readback.
+    if (gfxDataSourceSurface != NULL) {
+        gfxDataSourceSurface->AddRef();
+    }
     return gfxDataSourceSurface;
 }
 
@@ -473,6 +839,223 @@ extern "C" int32_t AzDataSourceSurfaceGetStride(AzDataSourceSurfaceRef aSurface)
     return gfxDataSourceSurface->Stride();
 }
 
+extern "C" AzDataSourceSurfaceRef
+AzCreateDataSourceSurface(const AzIntSize *aSize, AzSurfaceFormat aFormat) {
+    const gfx::IntSize *gfxSize = reinterpret_cast<const gfx::IntSize*>(aSize);
+    gfx::SurfaceFormat gfxFormat = static_cast<gfx::SurfaceFormat>(aFormat);
+    RefPtr<gfx::DataSourceSurface> gfxDataSourceSurface =
+        gfx::Factory::CreateDataSourceSurface(*gfxSize, gfxFormat);
+    if (gfxDataSourceSurface != NULL) {
+        gfxDataSourceSurface->AddRef();
+    }
+    return gfxDataSourceSurface;
+}
+
+extern "C" AzIntSize
+AzCalculateBlurRadius(const AzPoint *aStandardDeviation) {
+    const gfx::Point *gfxStandardDeviation = reinterpret_cast<const gfx::Point*>(aStandardDeviation);
+    gfx::IntSize gfxRadius = gfx::AlphaBoxBlur::CalculateBlurRadius(*gfxStandardDeviation);
+    AzIntSize radius = { gfxRadius.width, gfxRadius.height };
+    return radius;
+}
+
+extern "C" AzDataSourceSurfaceRef
+AzBlurDataSourceSurface(AzDataSourceSurfaceRef aSurface,
+                        const AzIntSize *aSpreadRadius,
+                        const AzIntSize *aBlurRadius,
+                        const AzRect *aDirtyRect,
+                        const AzRect *aSkipRect,
+                        AzIntRect *aResultRect) {
+    gfx::DataSourceSurface *gfxSource = static_cast<gfx::DataSourceSurface*>(aSurface);
+    const gfx::IntSize *gfxSpreadRadius = reinterpret_cast<const gfx::IntSize*>(aSpreadRadius);
+    const gfx::IntSize *gfxBlurRadius = reinterpret_cast<const gfx::IntSize*>(aBlurRadius);
+    const gfx::Rect *gfxDirtyRect = reinterpret_cast<const gfx::Rect*>(aDirtyRect);
+    const gfx::Rect *gfxSkipRect = reinterpret_cast<const gfx::Rect*>(aSkipRect);
+
+    gfx::SurfaceFormat format = gfxSource->GetFormat();
+    int32_t channels;
+    if (format == gfx::FORMAT_A8) {
+        channels = 1;
+    } else if (format == gfx::FORMAT_B8G8R8A8) {
+        channels = 4;
+    } else {
+        return NULL;
+    }
+
+    gfx::IntSize size = gfxSource->GetSize();
+    gfx::AlphaBoxBlur blur(gfx::Rect(0, 0, size.width, size.height),
+                           *gfxSpreadRadius, *gfxBlurRadius, gfxDirtyRect, gfxSkipRect);
+    gfx::IntRect blurRect = blur.GetRect();
+    uint8_t *blurData = blur.GetData();
+    if (blurRect.IsEmpty() || !blurData) {
+        return NULL;
+    }
+
+    RefPtr<gfx::DataSourceSurface> gfxResult =
+        gfx::Factory::CreateDataSourceSurface(blurRect.Size(), format);
+    if (gfxResult == NULL) {
+        return NULL;
+    }
+
+    // AlphaBoxBlur only blurs 8-bit surfaces, so each channel of a BGRA surface is blurred on
+    // its own. Spreading and blurring premultiplied channels separately keeps them
+    // premultiplied.
+    gfx::IntRect sourceRect = gfx::IntRect(0, 0, size.width, size.height).Intersect(blurRect);
+    uint8_t *sourceData = gfxSource->GetData();
+    int32_t sourceStride = gfxSource->Stride();
+    uint8_t *resultData = gfxResult->GetData();
+    int32_t resultStride = gfxResult->Stride();
+    int32_t blurStride = blur.GetStride();
+    for (int32_t channel = 0; channel < channels; channel++) {
+        memset(blurData, 0, blurStride * blurRect.height);
+        for (int32_t y = sourceRect.y; y < sourceRect.YMost(); y++) {
+            uint8_t *source = sourceData + y * sourceStride + channel;
+            uint8_t *dest = blurData + (y - blurRect.y) * blurStride - blurRect.x;
+            for (int32_t x = sourceRect.x; x < sourceRect.XMost(); x++) {
+                dest[x] = source[x * channels];
+            }
+        }
+
+        blur.Blur();
+
+        for (int32_t y = 0; y < blurRect.height; y++) {
+            uint8_t *source = blurData + y * blurStride;
+            uint8_t *dest = resultData + y * resultStride + channel;
+            for (int32_t x = 0; x < blurRect.width; x++) {
+                dest[x * channels] = source[x];
+            }
+        }
+    }
+
+    AzIntRect resultRect = { blurRect.x, blurRect.y, blurRect.width, blurRect.height };
+    *aResultRect = resultRect;
+    gfxResult->AddRef();
+    return gfxResult;
+}
+
+extern "C" AzDataSourceSurfaceRef
+AzScaleDataSourceSurface(AzDataSourceSurfaceRef aSurface,
+                         const AzIntSize *aSize,
+                         AzResizeMethod aMethod) {
+#ifdef USE_SKIA
+    gfx::DataSourceSurface *gfxSource = static_cast<gfx::DataSourceSurface*>(aSurface);
+    const gfx::IntSize *gfxSize = reinterpret_cast<const gfx::IntSize*>(aSize);
+
+    skia::ImageOperations::ResizeMethod method;
+    switch (aMethod) {
+    case AZ_RESIZE_GOOD:     method = skia::ImageOperations::RESIZE_GOOD; break;
+    case AZ_RESIZE_BETTER:   method = skia::ImageOperations::RESIZE_BETTER; break;
+    case AZ_RESIZE_BEST:     method = skia::ImageOperations::RESIZE_BEST; break;
+    case AZ_RESIZE_BOX:      method = skia::ImageOperations::RESIZE_BOX; break;
+    case AZ_RESIZE_HAMMING1: method = skia::ImageOperations::RESIZE_HAMMING1; break;
+    case AZ_RESIZE_LANCZOS2: method = skia::ImageOperations::RESIZE_LANCZOS2; break;
+    case AZ_RESIZE_LANCZOS3: method = skia::ImageOperations::RESIZE_LANCZOS3; break;
+    default: return NULL;
+    }
+
+    gfx::SurfaceFormat format = gfxSource->GetFormat();
+    if (format != gfx::FORMAT_B8G8R8A8 && format != gfx::FORMAT_B8G8R8X8 &&
+        format != gfx::FORMAT_A8) {
+        return NULL;
+    }
+
+    gfx::IntSize size = gfxSource->GetSize();
+    uint8_t *sourceData = gfxSource->GetData();
+    int32_t sourceStride = gfxSource->Stride();
+
+    // The resizer only handles 32-bit pixels, so each alpha value of an A8 surface is spread
+    // over a premultiplied white pixel and read back from the alpha channel afterwards.
+    std::vector<uint32_t> expanded;
+    SkBitmap source;
+    if (format == gfx::FORMAT_A8) {
+        expanded.resize(size.width * size.height);
+        for (int32_t y = 0; y < size.height; y++) {
+            for (int32_t x = 0; x < size.width; x++) {
+                expanded[y * size.width + x] = sourceData[y * sourceStride + x] * 0x01010101u;
+            }
+        }
+        source.setConfig(SkBitmap::kARGB_8888_Config, size.width, size.height, size.width * 4);
+        source.setPixels(expanded.empty() ? NULL : &expanded[0]);
+    } else {
+        source.setConfig(SkBitmap::kARGB_8888_Config, size.width, size.height, sourceStride);
+        source.setPixels(sourceData);
+    }
+    source.setIsOpaque(format == gfx::FORMAT_B8G8R8X8);
+
+    SkBitmap result = skia::ImageOperations::Resize(source, method,
+                                                    gfxSize->width, gfxSize->height);
+    if (result.isNull()) {
+        return NULL;
+    }
+
+    RefPtr<gfx::DataSourceSurface> gfxResult =
+        gfx::Factory::CreateDataSourceSurface(*gfxSize, format);
+    if (gfxResult == NULL) {
+        return NULL;
+    }
+
+    SkAutoLockPixels lock(result);
+    uint8_t *resultData = gfxResult->GetData();
+    int32_t resultStride = gfxResult->Stride();
+    for (int32_t y = 0; y < gfxSize->height; y++) {
+        uint8_t *dest = resultData + y * resultStride;
+        if (format == gfx::FORMAT_A8) {
+            const uint32_t *row = result.getAddr32(0, y);
+            for (int32_t x = 0; x < gfxSize->width; x++) {
+                dest[x] = SkGetPackedA32(row[x]);
+            }
+        } else {
+            memcpy(dest, result.getAddr32(0, y), gfxSize->width * 4);
+        }
+    }
+
+    gfxResult->AddRef();
+    return gfxResult;
+#else
+    return NULL;
+#endif
+}
+
+extern "C" AzDataSourceSurfaceRef
+AzHalfScaleDataSourceSurface(AzDataSourceSurfaceRef aSurface, const AzIntSize *aSize) {
+    gfx::DataSourceSurface *gfxSource = static_cast<gfx::DataSourceSurface*>(aSurface);
+    const gfx::IntSize *gfxSize = reinterpret_cast<const gfx::IntSize*>(aSize);
+
+    gfx::SurfaceFormat format = gfxSource->GetFormat();
+    if (format != gfx::FORMAT_B8G8R8A8 && format != gfx::FORMAT_B8G8R8X8) {
+        return NULL;
+    }
+
+    gfx::IntSize size = gfxSource->GetSize();
+    uint8_t *scaledData = gfxSource->GetData();
+    int32_t scaledStride = gfxSource->Stride();
+    gfx::IntSize scaledSize = size;
+
+    // ScaleForSize leaves the scaler without any data when no halving is needed.
+    gfx::ImageHalfScaler scaler(gfxSource->GetData(), gfxSource->Stride(), size);
+    if (size.width / 2 > gfxSize->width || size.height / 2 > gfxSize->height) {
+        scaler.ScaleForSize(*gfxSize);
+        scaledData = scaler.GetScaledData();
+        scaledStride = scaler.GetStride();
+        scaledSize = scaler.GetSize();
+    }
+
+    RefPtr<gfx::DataSourceSurface> gfxResult =
+        gfx::Factory::CreateDataSourceSurface(scaledSize, format);
+    if (gfxResult == NULL) {
+        return NULL;
+    }
+
+    uint8_t *resultData = gfxResult->GetData();
+    int32_t resultStride = gfxResult->Stride();
+    for (int32_t y = 0; y < scaledSize.height; y++) {
+        memcpy(resultData + y * resultStride, scaledData + y * scaledStride, scaledSize.width * 4);
+    }
+
+    gfxResult->AddRef();
+    return gfxResult;
+}
+
 extern "C" AzScaledFontRef
 AzCreateScaledFontForNativeFont(AzNativeFont *aNativeFont, AzFloat aSize) {
     gfx::NativeFont *gfxNativeFont = reinterpret_cast<gfx::NativeFont*>(aNativeFont);
@@ -481,12 +1064,101 @@ AzCreateScaledFontForNativeFont(AzNativeFont *aNativeFont, AzFloat aSize) {
     return font;
 }
 
+extern "C" AzScaledFontRef
+AzCreateScaledFontForTrueTypeData(uint8_t *aData, uint32_t aSize, uint32_t aFaceIndex,
+                                  AzFloat aGlyphSize, AzFontType aType,
+                                  AzFontDataReleaseFunc aRelease, void *aClosure) {
+    gfx::FontType gfxType = static_cast<gfx::FontType>(aType);
+    RefPtr<gfx::ScaledFont> font = gfx::Factory::CreateScaledFontForTrueTypeData(aData, aSize,
+                                                                                aFaceIndex,
+                                                                                aGlyphSize,
+                                                                                gfxType,
+                                                                                aRelease,
+                                                                                aClosure);
+    if (font != NULL) {
+        font->AddRef();
+    }
+    return font;
+}
+
+extern "C" AzScaledFontRef
+AzCreateScaledFontWithCairo(AzNativeFont *aNativeFont, AzFloat aSize,
+                            AzCairoScaledFontRef aScaledFont) {
+    gfx::NativeFont *gfxNativeFont = reinterpret_cast<gfx::NativeFont*>(aNativeFont);
+    cairo_scaled_font_t *cairoScaledFont = static_cast<cairo_scaled_font_t*>(aScaledFont);
+    RefPtr<gfx::ScaledFont> font = gfx::Factory::CreateScaledFontWithCairo(*gfxNativeFont,
+                                                                          aSize,
+                                                                          cairoScaledFont);
+    if (font != NULL) {
+        font->AddRef();
+    }
+    return font;
+}
+
 extern "C" void
 AzReleaseScaledFont(AzScaledFontRef aFont) {
     gfx::ScaledFont *gfxFont = static_cast<gfx::ScaledFont*>(aFont);
     gfxFont->Release();
 }
 
+extern "C" AzPathRef
+AzScaledFontGetPathForGlyphs(AzScaledFontRef aFont,
+                             const AzGlyphBuffer *aGlyphBuffer,
+                             AzDrawTargetRef aDrawTarget) {
+    gfx::ScaledFont *gfxFont = static_cast<gfx::ScaledFont*>(aFont);
+    const gfx::GlyphBuffer *gfxGlyphBuffer = reinterpret_cast<const gfx::GlyphBuffer*>(aGlyphBuffer);
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    RefPtr<gfx::Path> gfxPath = gfxFont->GetPathForGlyphs(*gfxGlyphBuffer, gfxDrawTarget);
+    if (gfxPath != NULL) {
+        gfxPath->AddRef();
+    }
+    return gfxPath;
+}
+
+extern "C" bool
+AzScaledFontGetMetrics(AzScaledFontRef aFont, AzFontMetrics *aMetrics) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(static_cast<gfx::ScaledFont*>(aFont));
+    gfx::FontMetrics *gfxMetrics = reinterpret_cast<gfx::FontMetrics*>(aMetrics);
+    return gfxFont->GetMetrics(*gfxMetrics);
+}
+
+extern "C" bool
+AzScaledFontGetGlyphMetrics(AzScaledFontRef aFont, uint32_t aGlyph,
+                            AzFloat *aAdvance, AzRect *aBounds) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(static_cast<gfx::ScaledFont*>(aFont));
+    gfx::Rect *gfxBounds = reinterpret_cast<gfx::Rect*>(aBounds);
+    return gfxFont->GetGlyphMetrics(aGlyph, aAdvance, gfxBounds);
+}
+
+extern "C" uint32_t
+AzScaledFontGetGlyphIndex(AzScaledFontRef aFont, uint32_t aCharacter) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(static_cast<gfx::ScaledFont*>(aFont));
+    return gfxFont->GetGlyphIndex(aCharacter);
+}
+
+extern "C" void
+AzScaledFontSetSyntheticStyle(AzScaledFontRef aFont, bool aBold, bool aOblique) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(static_cast<gfx::ScaledFont*>(aFont));
+    gfxFont->SetSyntheticStyle(aBold, aOblique);
+}
+
+extern "C" bool
+AzScaledFontCopyGlyphsToBuilder(AzScaledFontRef aFont,
+                                const AzGlyphBuffer *aGlyphBuffer,
+                                AzPathBuilderRef aPathBuilder) {
+    gfx::ScaledFontBase *gfxFont = static_cast<gfx::ScaledFontBase*>(static_cast<gfx::ScaledFont*>(aFont));
+    const gfx::GlyphBuffer *gfxGlyphBuffer = reinterpret_cast<const gfx::GlyphBuffer*>(aGlyphBuffer);
+    gfx::PathBuilder *gfxPathBuilder = static_cast<gfx::PathBuilder*>(aPathBuilder);
+    // Only fonts with a Skia typeface can give their outlines to builders.
+    #ifdef USE_SKIA
+    if (gfxFont->GetSkTypeface()) {
+        gfxFont->CopyGlyphsToBuilder(*gfxGlyphBuffer, gfxPathBuilder);
+        return true;
+    }
+    #endif
+    return false;
+}
+
 extern "C" void
 AzDrawTargetSetTransform(AzDrawTargetRef aDrawTarget,
                          AzMatrix *aTransform) {
@@ -495,14 +1167,60 @@ AzDrawTargetSetTransform(AzDrawTargetRef aDrawTarget,
     gfxDrawTarget->SetTransform(*gfxMatrix);
 }
 
+extern "C" AzMatrix
+AzDrawTargetGetTransform(AzDrawTargetRef aDrawTarget) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    return MatrixToC(gfxDrawTarget->GetTransform());
+}
+
+extern "C" void
+AzDrawTargetConcatTransform(AzDrawTargetRef aDrawTarget,
+                            const AzMatrix *aTransform) {
+    gfx::DrawTarget *gfxDrawTarget = static_cast<gfx::DrawTarget*>(aDrawTarget);
+    const gfx::Matrix *gfxMatrix = reinterpret_cast<const gfx::Matrix*>(aTransform);
+    // aTransform applies first, in the current user space.
+    gfxDrawTarget->SetTransform(*gfxMatrix * gfxDrawTarget->GetTransform());
+}
+
+extern "C" AzMatrix
+AzMatrixRotation(AzFloat aAngle) {
+    return MatrixToC(gfx::Matrix::Rotation(aAngle));
+}
+
+extern "C" bool
+AzMatrixInvert(AzMatrix *aMatrix) {
+    gfx::Matrix *gfxMatrix = reinterpret_cast<gfx::Matrix*>(aMatrix);
+    return gfxMatrix->Invert();
+}
+
+extern "C" AzRect
+AzMatrixTransformBounds(const AzMatrix *aMatrix, const AzRect *aRect) {
+    const gfx::Matrix *gfxMatrix = reinterpret_cast<const gfx::Matrix*>(aMatrix);
+    const gfx::Rect *gfxRect = reinterpret_cast<const gfx::Rect*>(aRect);
+    return RectToC(gfxMatrix->TransformBounds(*gfxRect));
+}
+
+extern "C" bool
+AzMatrixIsIdentity(const AzMatrix *aMatrix) {
+    const gfx::Matrix *gfxMatrix = reinterpret_cast<const gfx::Matrix*>(aMatrix);
+    return gfxMatrix->IsIdentity();
+}
+
+extern "C" bool
+AzMatrixIsRectilinear(const AzMatrix *aMatrix) {
+    const gfx::Matrix *gfxMatrix = reinterpret_cast<const gfx::Matrix*>(aMatrix);
+    return gfxMatrix->IsRectilinear();
+}
+
 extern "C" AzFontOptions*
-AzCreateFontOptionsForName(char *aName, AzFontStyle aStyle) {
+AzCreateFontOptionsForName(const char *aName, AzFontStyle aStyle) {
     #ifdef MOZ_ENABLE_FREETYPE
     gfx::FontOptions *options = new gfx::FontOptions;
     options->mName = std::string(aName);
     options->mStyle = static_cast<gfx::FontStyle>(aStyle);
     options->mData = NULL;
     options->mDataSize = 0;
+    options->mFaceIndex = 0;
     return options;
     #else
     abort();
@@ -510,12 +1228,108 @@ AzCreateFontOptionsForName(char *aName, AzFontStyle aStyle) {
 }
 
 extern "C" AzFontOptions*
-AzCreateFontOptionsForData(uint8_t *aFontData, uint32_t aFontDataSize) {
+AzCreateFontOptionsForData(const uint8_t *aFontData, uint32_t aFontDataSize) {
     #ifdef MOZ_ENABLE_FREETYPE
     gfx::FontOptions *options = new gfx::FontOptions;
     options->mStyle = gfx::FONT_STYLE_NORMAL;
-    options->mData = aFontData;
+    options->mData = const_cast<uint8_t*>(aFontData);
     options->mDataSize = aFontDataSize;
+    options->mFaceIndex = 0;
+    return options;
+    #else
+    abort();
+    #endif
+}
+
+#ifdef MOZ_ENABLE_FONTCONFIG
+// Maps a CSS font weight onto the fontconfig weight scale.
+static int
+CSSWeightToFontconfig(uint16_t aWeight)
+{
+    static const int sWeights[] = {
+        FC_WEIGHT_THIN, FC_WEIGHT_EXTRALIGHT, FC_WEIGHT_LIGHT, FC_WEIGHT_REGULAR,
+        FC_WEIGHT_MEDIUM, FC_WEIGHT_DEMIBOLD, FC_WEIGHT_BOLD, FC_WEIGHT_EXTRABOLD,
+        FC_WEIGHT_BLACK
+    };
+    int index = (aWeight + 50) / 100 - 1;
+    return sWeights[index < 0 ? 0 : (index > 8 ? 8 : index)];
+}
+#endif
+
+extern "C" AzFontOptions*
+AzCreateFontOptionsForDescriptor(const char *aFamily, uint16_t aWeight, AzFontSlant aSlant,
+                                 uint16_t aStretch) {
+    #ifdef MOZ_ENABLE_FREETYPE
+    gfx::FontOptions *options = new gfx::FontOptions;
+    // Used as is when fontconfig is unavailable or has no match.
+    bool bold = aWeight >= 600;
+    bool italic = aSlant != AZ_FONT_SLANT_NORMAL;
+    options->mName = std::string(aFamily);
+    options->mStyle = bold ? (italic ? gfx::FONT_STYLE_BOLD_ITALIC : gfx::FONT_STYLE_BOLD)
+                           : (italic ? gfx::FONT_STYLE_ITALIC : gfx::FONT_STYLE_NORMAL);
+    options->mData = NULL;
+    options->mDataSize = 0;
+    options->mFaceIndex = 0;
+
+    #ifdef MOZ_ENABLE_FONTCONFIG
+    int slant = FC_SLANT_ROMAN;
+    if (aSlant == AZ_FONT_SLANT_ITALIC) {
+        slant = FC_SLANT_ITALIC;
+    } else if (aSlant == AZ_FONT_SLANT_OBLIQUE) {
+        slant = FC_SLANT_OBLIQUE;
+    }
+
+    FcPattern *pattern = FcPatternCreate();
+    FcPatternAddString(pattern, FC_FAMILY, reinterpret_cast<const FcChar8*>(aFamily));
+    FcPatternAddInteger(pattern, FC_WEIGHT, CSSWeightToFontconfig(aWeight));
+    FcPatternAddInteger(pattern, FC_SLANT, slant);
+    // CSS stretch percentages and fontconfig widths use the same scale.
+    FcPatternAddInteger(pattern, FC_WIDTH, aStretch);
+    FcConfigSubstitute(NULL, pattern, FcMatchPattern);
+    FcDefaultSubstitute(pattern);
+
+    FcResult result;
+    FcPattern *match = FcFontMatch(NULL, pattern, &result);
+    if (match) {
+        FcChar8 *file;
+        int index;
+        if (FcPatternGetString(match, FC_FILE, 0, &file) == FcResultMatch) {
+            options->mFile = std::string(reinterpret_cast<const char*>(file));
+            if (FcPatternGetInteger(match, FC_INDEX, 0, &index) == FcResultMatch) {
+                options->mFaceIndex = index & 0xffff;
+            }
+        }
+        FcPatternDestroy(match);
+    }
+    FcPatternDestroy(pattern);
+    #endif
+
+    return options;
+    #else
+    abort();
+    #endif
+}
+
+extern "C" AzFontOptions*
+AzCreateFontOptionsForFreetypeFace(void *aFace) {
+    #ifdef MOZ_ENABLE_FREETYPE
+    FT_Face face = static_cast<FT_Face>(aFace);
+    gfx::FontOptions *options = new gfx::FontOptions;
+    options->mName = std::string(face->family_name ? face->family_name : "");
+    // FreeType's style flags and FontStyle agree in the lowest two bits.
+    options->mStyle = static_cast<gfx::FontStyle>(face->style_flags & 3);
+    options->mData = NULL;
+    options->mDataSize = 0;
+    // The upper bits of the face index select variation instances.
+    options->mFaceIndex = face->face_index & 0xffff;
+    // Prefer the file, which every font of the face shares, to the memory FreeType may have
+    // mapped it into, which each font would have to copy.
+    if (face->stream->pathname.pointer) {
+        options->mFile = std::string(static_cast<const char*>(face->stream->pathname.pointer));
+    } else if (face->stream->base) {
+        options->mData = face->stream->base;
+        options->mDataSize = face->stream->size;
+    }
     return options;
     #else
     abort();
@@ -532,6 +1346,33 @@ AzDestroyFontOptions(AzFontOptions* aOptions) {
     #endif
 }
 
+extern "C" AzCairoScaledFontRef
+AzCreateCairoScaledFontForFreetypeFace(void *aFace, AzFloat aSize) {
+    #if defined(USE_CAIRO) && defined(MOZ_ENABLE_FREETYPE)
+    FT_Face face = static_cast<FT_Face>(aFace);
+    cairo_font_face_t *fontFace = cairo_ft_font_face_create_for_ft_face(face, 0);
+    cairo_matrix_t fontMatrix, ctm;
+    cairo_matrix_init_scale(&fontMatrix, aSize, aSize);
+    cairo_matrix_init_identity(&ctm);
+    cairo_font_options_t *options = cairo_font_options_create();
+    cairo_scaled_font_t *scaledFont = cairo_scaled_font_create(fontFace, &fontMatrix, &ctm, options);
+    cairo_font_options_destroy(options);
+    cairo_font_face_destroy(fontFace);
+    return scaledFont;
+    #else
+    abort();
+    #endif
+}
+
+extern "C" void
+AzReleaseCairoScaledFont(AzCairoScaledFontRef aFont) {
+    #ifdef USE_CAIRO
+    cairo_scaled_font_destroy(static_cast<cairo_scaled_font_t*>(aFont));
+    #else
+    abort();
+    #endif
+}
+
 extern "C" AzGLContext
 AzSkiaGetCurrentGLContext() {
     return SkNativeSharedGLContext::GetCurrent();
@@ -591,6 +1432,13 @@ AzReleasePath(AzPathRef aPath) {
     gfxPath->Release();
 }
 
+extern "C" AzRect
+AzPathGetBounds(AzPathRef aPath, const AzMatrix *aTransform) {
+    gfx::Path *gfxPath = static_cast<gfx::Path*>(aPath);
+    const gfx::Matrix *gfxMatrix = reinterpret_cast<const gfx::Matrix*>(aTransform);
+    return RectToC(gfxPath->GetBounds(*gfxMatrix));
+}
+
 extern "C" AzLinearGradientPatternRef
 AzCreateLinearGradientPattern(const AzPoint *aBegin,
                               const AzPoint *aEnd,
@@ -615,3 +1463,25 @@ void AzReleaseGradientStops(AzGradientStopsRef aStops) {
     gfxStops->Release();
 }
 
+extern "C" AzGlyphRenderingOptionsRef
+AzCreateSkiaGlyphRenderingOptions(AzFontHinting aHinting, bool aAutohinter,
+                                  bool aSubpixelPositioning, bool aLCDText,
+                                  AzFloat aGamma, AzFloat aContrast, bool aColorGlyphs) {
+#ifdef USE_SKIA
+    gfx::FontHinting gfxHinting = static_cast<gfx::FontHinting>(aHinting);
+    RefPtr<gfx::GlyphRenderingOptions> options =
+        new gfx::GlyphRenderingOptionsSkia(gfxHinting, aAutohinter, aSubpixelPositioning,
+                                           aLCDText, aGamma, aContrast, aColorGlyphs);
+    options->AddRef();
+    return options;
+#else
+    abort();
+#endif
+}
+
+extern "C" void
+AzReleaseGlyphRenderingOptions(AzGlyphRenderingOptionsRef aOptions) {
+    gfx::GlyphRenderingOptions *gfxOptions = static_cast<gfx::GlyphRenderingOptions*>(aOptions);
+    gfxOptions->Release();
+}
+
@@ -19,6 +19,15 @@ extern "C" {
 // A function that we use to test that our definitions match the C++
 void AzSanityCheck();
 
+// The size of a struct, the offset of one of its fields ("AzRect.width"), or the value of an
+// enum constant, so that bindings can verify their own definitions against ours.
+typedef struct _AzLayoutEntry {
+  const char *mName;
+  int64_t mValue;
+} AzLayoutEntry;
+
+const AzLayoutEntry *AzGetLayoutEntries(uint32_t *aCount);
+
 
 // FIXME: This stuff is copy pasted from the azure headers
 
@@ -40,6 +49,8 @@ typedef void* AzGLContextMetadataRef;
 typedef void* AzPathBuilderRef;
 typedef void* AzPathRef;
 typedef void* AzLinearGradientPatternRef;
+typedef void* AzCairoSurfaceRef;
+typedef void* AzCairoScaledFontRef;
 
 /* Types.h */
 
@@ -63,7 +74,10 @@ enum AzSurfaceFormat
   AZ_FORMAT_B8G8R8A8,
   AZ_FORMAT_B8G8R8X8,
   AZ_FORMAT_R5G6B5,
-  AZ_FORMAT_A8
+  AZ_FORMAT_A8,
+  AZ_FORMAT_R8G8B8A8,
+  AZ_FORMAT_R8G8B8X8,
+  AZ_FORMAT_R16G16B16A16
 };
 
 enum AzBackendType
@@ -73,7 +87,8 @@ enum AzBackendType
   AZ_BACKEND_COREGRAPHICS,
   AZ_BACKEND_COREGRAPHICS_ACCELERATED,
   AZ_BACKEND_CAIRO,
-  AZ_BACKEND_SKIA
+  AZ_BACKEND_SKIA,
+  AZ_BACKEND_RECORDING
 };
 
 enum AzFontType
@@ -110,6 +125,21 @@ enum AzFontStyle
   AZ_FONT_STYLE_BOLD_ITALIC
 };
 
+enum AzFontHinting
+{
+  AZ_FONT_HINTING_NONE,
+  AZ_FONT_HINTING_LIGHT,
+  AZ_FONT_HINTING_NORMAL,
+  AZ_FONT_HINTING_FULL
+};
+
+enum AzFontSlant
+{
+  AZ_FONT_SLANT_NORMAL,
+  AZ_FONT_SLANT_ITALIC,
+  AZ_FONT_SLANT_OBLIQUE
+};
+
 enum AzCompositionOp {
   AZ_OP_OVER,
   AZ_OP_ADD,
@@ -154,7 +184,8 @@ enum AzFillRule {
 enum AzAntialiasMode {
   AZ_AA_NONE,
   AZ_AA_GRAY,
-  AZ_AA_SUBPIXEL
+  AZ_AA_SUBPIXEL,
+  AZ_AA_DEFAULT
 };
 
 enum AzSnapping {
@@ -199,6 +230,16 @@ enum AzSide {
   AZ_eSideLeft
 };
 
+enum AzResizeMethod {
+  AZ_RESIZE_GOOD,
+  AZ_RESIZE_BETTER,
+  AZ_RESIZE_BEST,
+  AZ_RESIZE_BOX,
+  AZ_RESIZE_HAMMING1,
+  AZ_RESIZE_LANCZOS2,
+  AZ_RESIZE_LANCZOS3
+};
+
 typedef struct _AzColor {
     AzFloat r, g, b, a;
 } AzColor;
@@ -299,6 +340,22 @@ typedef struct _AzNativeFont {
   void *mFont;
 } AzNativeFont;
 
+typedef struct _AzFontMetrics {
+  AzFloat mAscent;
+  AzFloat mDescent;
+  AzFloat mLeading;
+  AzFloat mXHeight;
+  AzFloat mUnderlinePosition;
+  AzFloat mUnderlineThickness;
+} AzFontMetrics;
+
+typedef struct _AzRectCornerRadii {
+  AzSize mTopLeft;
+  AzSize mTopRight;
+  AzSize mBottomRight;
+  AzSize mBottomLeft;
+} AzRectCornerRadii;
+
 typedef GrGLSharedContext AzGLContext;
 typedef GrGLNativeContext* AzGLNativeContextRef;
 
@@ -312,6 +369,14 @@ unsigned int AzSkiaSharedGLContextGetFBOID(AzSkiaSharedGLContextRef aGLContext);
 AzSkiaGrContextRef AzSkiaSharedGLContextGetGrContext(AzSkiaSharedGLContextRef aGLContext);
 void AzSkiaSharedGLContextMakeCurrent(AzSkiaSharedGLContextRef aGLContext);
 void AzSkiaSharedGLContextFlush(AzSkiaSharedGLContextRef aGLContext);
+AzSkiaGrGLSharedSurfaceRef AzSkiaSharedGLContextStealSurface(AzSkiaSharedGLContextRef aGLContext);
+
+/* Whether the processor supports SSE2, whether Azure's own SSE2 code paths were compiled in,
+ * and whether they're taken, which needs both and AzSetSSE2Disabled(false). */
+bool AzCPUSupportsSSE2();
+bool AzSSE2PathsCompiled();
+bool AzHasSSE2();
+void AzSetSSE2Disabled(bool aDisabled);
 
 AzDrawTargetRef AzCreateDrawTarget(AzBackendType aBackend,
                                    AzIntSize *aSize,
@@ -321,17 +386,33 @@ AzDrawTargetRef AzCreateDrawTargetForData(AzBackendType aBackend,
                                           AzIntSize *aSize,
                                           int32_t aStride,
                                           AzSurfaceFormat aFormat);
-AzDrawTargetRef AzCreateSkiaDrawTragetForFBO(AzSkiaSharedGLContextRef aGLContext,
+AzDrawTargetRef AzCreateDualDrawTarget(AzDrawTargetRef aTargetA,
+                                       AzDrawTargetRef aTargetB);
+AzDrawTargetRef AzCreateSkiaDrawTargetForFBO(AzSkiaSharedGLContextRef aGLContext,
                                              AzIntSize *aSize,
                                              AzSurfaceFormat aFormat);
 
+AzDrawTargetRef AzCreateDrawTargetForCairoSurface(AzCairoSurfaceRef aSurface,
+                                                  AzIntSize *aSize);
+
 void AzRetainDrawTarget(AzDrawTargetRef aTarget);
 
 void AzReleaseDrawTarget(AzDrawTargetRef aTarget);
 
 AzIntSize AzDrawTargetGetSize(AzDrawTargetRef aDrawTarget);
 void AzDrawTargetFlush(AzDrawTargetRef aDrawTarget);
+AzSurfaceFormat AzDrawTargetGetFormat(AzDrawTargetRef aDrawTarget);
+bool AzDrawTargetReadPixels(AzDrawTargetRef aDrawTarget,
+                            const AzIntRect *aRect,
+                            uint8_t *aData,
+                            int32_t aStride);
+AzDataSourceSurfaceRef AzDrawTargetSnapshotRect(AzDrawTargetRef aDrawTarget,
+                                                const AzIntRect *aRect);
 void AzDrawTargetClearRect(AzDrawTargetRef aDrawTarget, AzRect *aRect);
+void AzDrawTargetFill(AzDrawTargetRef aDrawTarget,
+                      AzPathRef aPath,
+                      AzPatternRef aPattern,
+                      AzDrawOptions *aDrawOptions);
 void AzDrawTargetFillRect(AzDrawTargetRef aDrawTarget,
 			              AzRect* aRect,
 			              AzPatternRef aPattern,
@@ -357,7 +438,7 @@ void AzDrawTargetDrawSurface(AzDrawTargetRef aDrawTarget,
                              AzSourceSurfaceRef aSurface,
                              AzRect *aDest,
                              AzRect *aSource,
-                             AzDrawSurfaceOptionsRef aSurfOptions,
+                             AzDrawSurfaceOptions *aSurfOptions,
                              AzDrawOptions *aOptions);
 void AzDrawTargetDrawSurfaceWithShadow(AzDrawTargetRef aDrawTarget,
                                        AzSourceSurfaceRef aSurface,
@@ -366,6 +447,17 @@ void AzDrawTargetDrawSurfaceWithShadow(AzDrawTargetRef aDrawTarget,
                                        const AzPoint* aOffset,
                                        AzFloat aSigma,
                                        AzCompositionOp aOperator);
+/* See DrawBoxShadow in BoxShadow.h. aClip may be null. */
+void AzDrawTargetDrawBoxShadow(AzDrawTargetRef aDrawTarget,
+                               const AzRect *aRect,
+                               const AzRectCornerRadii *aRadii,
+                               const AzColor *aColor,
+                               const AzPoint *aOffset,
+                               AzFloat aSpread,
+                               AzFloat aBlurSigma,
+                               bool aInset,
+                               const AzRect *aClip,
+                               AzDrawOptions *aDrawOptions);
 AzSourceSurfaceRef AzDrawTargetGetSnapshot(AzDrawTargetRef aDrawTarget);
 AzSourceSurfaceRef AzDrawTargetCreateSourceSurfaceFromData(AzDrawTargetRef aDrawTarget,
                                                            const unsigned char *aData,
@@ -373,12 +465,21 @@ AzSourceSurfaceRef AzDrawTargetCreateSourceSurfaceFromData(AzDrawTargetRef aDraw
                                                            int32_t aStride,
                                                            AzSurfaceFormat aFormat);
 AzGradientStopsRef AzDrawTargetCreateGradientStops(AzDrawTargetRef aDrawTarget,
-                                                   AzGradientStop *aStops,
+                                                   const AzGradientStop *aStops,
                                                    uint32_t aNumStops,
                                                    AzExtendMode aExtendMode);
 void AzDrawTargetPushClip(AzDrawTargetRef aDrawTarget, AzPathRef aPath);
 void AzDrawTargetPushClipRect(AzDrawTargetRef aDrawTarget, const AzRect *aRect);
 void AzDrawTargetPopClip(AzDrawTargetRef aDrawTarget);
+void AzDrawTargetSetTransform(AzDrawTargetRef aDrawTarget, AzMatrix *aTransform);
+AzMatrix AzDrawTargetGetTransform(AzDrawTargetRef aDrawTarget);
+void AzDrawTargetConcatTransform(AzDrawTargetRef aDrawTarget, const AzMatrix *aTransform);
+
+AzMatrix AzMatrixRotation(AzFloat aAngle);
+bool AzMatrixInvert(AzMatrix *aMatrix);
+AzRect AzMatrixTransformBounds(const AzMatrix *aMatrix, const AzRect *aRect);
+bool AzMatrixIsIdentity(const AzMatrix *aMatrix);
+bool AzMatrixIsRectilinear(const AzMatrix *aMatrix);
 
 void AzReleaseSourceSurface(AzSourceSurfaceRef aSurface);
 AzIntSize AzSourceSurfaceGetSize(AzSourceSurfaceRef aSurface);
@@ -387,6 +488,41 @@ AzDataSourceSurfaceRef AzSourceSurfaceGetDataSurface(AzSourceSurfaceRef aSurface
 uint8_t *AzDataSourceSurfaceGetData(AzDataSourceSurfaceRef aSurface);
 int32_t AzDataSourceSurfaceGetStride(AzDataSourceSurfaceRef aSurface);
 
+/* Creates a zero-filled surface in memory. */
+AzDataSourceSurfaceRef AzCreateDataSourceSurface(const AzIntSize *aSize, AzSurfaceFormat aFormat);
+
+/* Blur.h */
+
+AzIntSize AzCalculateBlurRadius(const AzPoint *aStandardDeviation);
+/* Spreads and blurs an A8 or B8G8R8A8 surface into a new surface, which is larger than the
+ * source by the spread and blur radii on every side. aDirtyRect and aSkipRect, which may be
+ * null, are in the source surface's coordinates; see AlphaBoxBlur. aResultRect receives the
+ * area the new surface covers, also in the source surface's coordinates. Returns null if
+ * that area is empty or the format isn't supported. */
+AzDataSourceSurfaceRef AzBlurDataSourceSurface(AzDataSourceSurfaceRef aSurface,
+                                               const AzIntSize *aSpreadRadius,
+                                               const AzIntSize *aBlurRadius,
+                                               const AzRect *aDirtyRect,
+                                               const AzRect *aSkipRect,
+                                               AzIntRect *aResultRect);
+
+/* Scale.h */
+
+/* Resamples a B8G8R8A8, B8G8R8X8 or A8 surface to aSize with Skia's image_operations
+ * filters. Returns null if either size is empty, the format isn't supported or Azure was
+ * built without Skia. */
+AzDataSourceSurfaceRef AzScaleDataSourceSurface(AzDataSourceSurfaceRef aSurface,
+                                                const AzIntSize *aSize,
+                                                AzResizeMethod aMethod);
+
+/* ImageScaling.h */
+
+/* Halves a B8G8R8A8 or B8G8R8X8 surface in each direction for as long as the result stays
+ * larger than aSize, averaging 2x2 blocks; see ImageHalfScaler. The surface is copied
+ * unchanged if no halving is needed. Returns null if the format isn't supported. */
+AzDataSourceSurfaceRef AzHalfScaleDataSourceSurface(AzDataSourceSurfaceRef aSurface,
+                                                    const AzIntSize *aSize);
+
 AzPathBuilderRef AzCreatePathBuilder(AzDrawTargetRef aDrawTarget);
 void AzReleasePathBuilder(AzPathBuilderRef aPathBuilder);
 void AzPathBuilderMoveTo(AzPathBuilderRef aPathBuilder, const AzPoint *aPoint);
@@ -398,6 +534,8 @@ void AzPathBuilderArc(AzPathBuilderRef aPathBuilder,
                       AzFloat aEndAngle,
                       bool aAntiClockwise);
 AzPathRef AzPathBuilderFinish(AzPathBuilderRef aPathBuilder);
+void AzReleasePath(AzPathRef aPath);
+AzRect AzPathGetBounds(AzPathRef aPath, const AzMatrix *aTransform);
 
 AzLinearGradientPatternRef AzCreateLinearGradientPattern(const AzPoint *aBegin,
                                                          const AzPoint *aEnd,
@@ -407,15 +545,58 @@ void AzReleasePattern(AzPatternRef aPattern);
 
 void AzReleaseGradientStops(AzGradientStopsRef aStops);
 
+AzGlyphRenderingOptionsRef AzCreateSkiaGlyphRenderingOptions(AzFontHinting aHinting,
+                                                             bool aAutohinter,
+                                                             bool aSubpixelPositioning,
+                                                             bool aLCDText,
+                                                             AzFloat aGamma,
+                                                             AzFloat aContrast,
+                                                             bool aColorGlyphs);
+void AzReleaseGlyphRenderingOptions(AzGlyphRenderingOptionsRef aOptions);
+
 /* Factory.h */
 
+AzScaledFontRef AzCreateScaledFontForNativeFont(AzNativeFont *aNativeFont, AzFloat aSize);
+/* Called once a font made by AzCreateScaledFontForTrueTypeData no longer reads its data. */
+typedef void (*AzFontDataReleaseFunc)(void *aClosure);
+
+AzScaledFontRef AzCreateScaledFontForTrueTypeData(uint8_t *aData,
+                                                  uint32_t aSize,
+                                                  uint32_t aFaceIndex,
+                                                  AzFloat aGlyphSize,
+                                                  AzFontType aType,
+                                                  AzFontDataReleaseFunc aRelease,
+                                                  void *aClosure);
+AzScaledFontRef AzCreateScaledFontWithCairo(AzNativeFont *aNativeFont,
+                                            AzFloat aSize,
+                                            AzCairoScaledFontRef aScaledFont);
 void AzReleaseScaledFont(AzScaledFontRef aFont);
+AzPathRef AzScaledFontGetPathForGlyphs(AzScaledFontRef aFont,
+                                       const AzGlyphBuffer *aGlyphBuffer,
+                                       AzDrawTargetRef aDrawTarget);
+bool AzScaledFontGetMetrics(AzScaledFontRef aFont, AzFontMetrics *aMetrics);
+bool AzScaledFontGetGlyphMetrics(AzScaledFontRef aFont,
+                                 uint32_t aGlyph,
+                                 AzFloat *aAdvance,
+                                 AzRect *aBounds);
+uint32_t AzScaledFontGetGlyphIndex(AzScaledFontRef aFont, uint32_t aCharacter);
+void AzScaledFontSetSyntheticStyle(AzScaledFontRef aFont, bool aBold, bool aOblique);
+bool AzScaledFontCopyGlyphsToBuilder(AzScaledFontRef aFont,
+                                     const AzGlyphBuffer *aGlyphBuffer,
+                                     AzPathBuilderRef aPathBuilder);
 
 /* Helpers */
 typedef void AzFontOptions;
-AzFontOptions* AzCreateFontOptionsForName(char *aName, AzFontStyle aStyle);
-AzFontOptions* AzCreateFontOptionsForData(uint8_t *aFontData, uint32_t aFontDataSize);
+AzFontOptions* AzCreateFontOptionsForName(const char *aName, AzFontStyle aStyle);
+AzFontOptions* AzCreateFontOptionsForData(const uint8_t *aFontData, uint32_t aFontDataSize);
+AzFontOptions* AzCreateFontOptionsForDescriptor(const char *aFamily,
+                                                uint16_t aWeight,
+                                                AzFontSlant aSlant,
+                                                uint16_t aStretch);
+AzFontOptions* AzCreateFontOptionsForFreetypeFace(void *aFace);
 void AzDestroyFontOptions(AzFontOptions* aOptions);
+AzCairoScaledFontRef AzCreateCairoScaledFontForFreetypeFace(void *aFace, AzFloat aSize);
+void AzReleaseCairoScaledFont(AzCairoScaledFontRef aFont);
 
 AzGLContext AzSkiaGetCurrentGLContext();
 
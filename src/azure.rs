@@ -2,7 +2,7 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-/* automatically generated by rust-bindgen */
+/* automatically generated by gen.py from azure-c.h; do not edit */
 
 #![allow(non_upper_case_globals)]
 #![allow(non_camel_case_types)]
@@ -10,7 +10,55 @@
 
 use libc::*;
 
-pub type AzFontOptions = *mut c_void;
+#[repr(C)]
+#[deriving(Clone)]
+pub struct struct__AzLayoutEntry {
+    pub mName: *const c_char,
+    pub mValue: int64_t,
+}
+
+pub type AzLayoutEntry = struct__AzLayoutEntry;
+
+pub type AzGradientStopsRef = *mut c_void;
+
+pub type AzSkiaSharedGLContextRef = *mut c_void;
+
+pub type AzSkiaSharedGrGLContextRef = *mut c_void;
+
+pub type AzSkiaGrContextRef = *mut c_void;
+
+pub type AzSkiaGrGLSharedSurfaceRef = *mut c_void;
+
+pub type AzSkiaGrGLSharedContextRef = *mut c_void;
+
+pub type AzDrawTargetRef = *mut c_void;
+
+pub type AzPatternRef = *mut c_void;
+
+pub type AzColorPatternRef = *mut c_void;
+
+pub type AzScaledFontRef = *mut c_void;
+
+pub type AzGlyphRenderingOptionsRef = *mut c_void;
+
+pub type AzSourceSurfaceRef = *mut c_void;
+
+pub type AzDrawSurfaceOptionsRef = *mut c_void;
+
+pub type AzDataSourceSurfaceRef = *mut c_void;
+
+pub type AzGLContextMetadataRef = *mut c_void;
+
+pub type AzPathBuilderRef = *mut c_void;
+
+pub type AzPathRef = *mut c_void;
+
+pub type AzLinearGradientPatternRef = *mut c_void;
+
+pub type AzCairoSurfaceRef = *mut c_void;
+
+pub type AzCairoScaledFontRef = *mut c_void;
+
 pub type AzFloat = c_float;
 
 pub type enum_AzSurfaceType = c_uint;
@@ -24,11 +72,16 @@ pub static AZ_SURFACE_COREGRAPHICS_CGCONTEXT: u32 = 6_u32;
 pub static AZ_SURFACE_SKIA: u32 = 7_u32;
 pub static AZ_SURFACE_DUAL_DT: u32 = 8_u32;
 
+pub type AzSurfaceType = enum_AzSurfaceType;
+
 pub type enum_AzSurfaceFormat = c_uint;
 pub static AZ_FORMAT_B8G8R8A8: u32 = 0_u32;
 pub static AZ_FORMAT_B8G8R8X8: u32 = 1_u32;
 pub static AZ_FORMAT_R5G6B5: u32 = 2_u32;
 pub static AZ_FORMAT_A8: u32 = 3_u32;
+pub static AZ_FORMAT_R8G8B8A8: u32 = 4_u32;
+pub static AZ_FORMAT_R8G8B8X8: u32 = 5_u32;
+pub static AZ_FORMAT_R16G16B16A16: u32 = 6_u32;
 
 pub type AzSurfaceFormat = enum_AzSurfaceFormat;
 
@@ -51,11 +104,15 @@ pub static AZ_FONT_SKIA: u32 = 3_u32;
 pub static AZ_FONT_CAIRO: u32 = 4_u32;
 pub static AZ_FONT_COREGRAPHICS: u32 = 5_u32;
 
+pub type AzFontType = enum_AzFontType;
+
 pub type enum_AzNativeSurfaceType = c_uint;
 pub static AZ_NATIVE_SURFACE_D3D10_TEXTURE: u32 = 0_u32;
 pub static AZ_NATIVE_SURFACE_CAIRO_SURFACE: u32 = 1_u32;
 pub static AZ_NATIVE_SURFACE_CGCONTEXT: u32 = 2_u32;
 
+pub type AzNativeSurfaceType = enum_AzNativeSurfaceType;
+
 pub type enum_AzNativeFontType = c_uint;
 pub static AZ_NATIVE_FONT_DWRITE_FONT_FACE: u32 = 0_u32;
 pub static AZ_NATIVE_FONT_GDI_FONT_FACE: u32 = 1_u32;
@@ -63,12 +120,31 @@ pub static AZ_NATIVE_FONT_MAC_FONT_FACE: u32 = 2_u32;
 pub static AZ_NATIVE_FONT_SKIA_FONT_FACE: u32 = 3_u32;
 pub static AZ_NATIVE_FONT_CAIRO_FONT_FACE: u32 = 4_u32;
 
+pub type AzNativeFontType = enum_AzNativeFontType;
+
 pub type enum_AzFontStyle = c_uint;
 pub static AZ_FONT_STYLE_NORMAL: u32 = 0_u32;
 pub static AZ_FONT_STYLE_ITALIC: u32 = 1_u32;
 pub static AZ_FONT_STYLE_BOLD: u32 = 2_u32;
 pub static AZ_FONT_STYLE_BOLD_ITALIC: u32 = 3_u32;
 
+pub type AzFontStyle = enum_AzFontStyle;
+
+pub type enum_AzFontHinting = c_uint;
+pub static AZ_FONT_HINTING_NONE: u32 = 0_u32;
+pub static AZ_FONT_HINTING_LIGHT: u32 = 1_u32;
+pub static AZ_FONT_HINTING_NORMAL: u32 = 2_u32;
+pub static AZ_FONT_HINTING_FULL: u32 = 3_u32;
+
+pub type AzFontHinting = enum_AzFontHinting;
+
+pub type enum_AzFontSlant = c_uint;
+pub static AZ_FONT_SLANT_NORMAL: u32 = 0_u32;
+pub static AZ_FONT_SLANT_ITALIC: u32 = 1_u32;
+pub static AZ_FONT_SLANT_OBLIQUE: u32 = 2_u32;
+
+pub type AzFontSlant = enum_AzFontSlant;
+
 pub type enum_AzCompositionOp = c_uint;
 pub static AZ_OP_OVER: u32 = 0_u32;
 pub static AZ_OP_ADD: u32 = 1_u32;
@@ -97,6 +173,7 @@ pub static AZ_OP_SATURATION: u32 = 23_u32;
 pub static AZ_OP_COLOR: u32 = 24_u32;
 pub static AZ_OP_LUMINOSITY: u32 = 25_u32;
 pub static AZ_OP_COUNT: u32 = 26_u32;
+
 pub type AzCompositionOp = enum_AzCompositionOp;
 
 pub type enum_AzExtendMode = c_uint;
@@ -104,19 +181,28 @@ pub static AZ_EXTEND_CLAMP: u32 = 0_u32;
 pub static AZ_EXTEND_REPEAT: u32 = 1_u32;
 pub static AZ_EXTEND_REFLECT: u32 = 2_u32;
 
+pub type AzExtendMode = enum_AzExtendMode;
+
 pub type enum_AzFillRule = c_uint;
 pub static AZ_FILL_WINDING: u32 = 0_u32;
 pub static AZ_FILL_EVEN_ODD: u32 = 1_u32;
 
+pub type AzFillRule = enum_AzFillRule;
+
 pub type enum_AzAntialiasMode = c_uint;
 pub static AZ_AA_NONE: u32 = 0_u32;
 pub static AZ_AA_GRAY: u32 = 1_u32;
 pub static AZ_AA_SUBPIXEL: u32 = 2_u32;
+pub static AZ_AA_DEFAULT: u32 = 3_u32;
+
+pub type AzAntialiasMode = enum_AzAntialiasMode;
 
 pub type enum_AzSnapping = c_uint;
 pub static AZ_SNAP_NONE: u32 = 0_u32;
 pub static AZ_SNAP_ALIGNED: u32 = 1_u32;
 
+pub type AzSnapping = enum_AzSnapping;
+
 pub type enum_AzFilter = c_uint;
 pub static AZ_FILTER_LINEAR: u32 = 0_u32;
 pub static AZ_FILTER_POINT: u32 = 1_u32;
@@ -129,27 +215,48 @@ pub static AZ_PATTERN_SURFACE: u32 = 1_u32;
 pub static AZ_PATTERN_LINEAR_GRADIENT: u32 = 2_u32;
 pub static AZ_PATTERN_RADIAL_GRADIENT: u32 = 3_u32;
 
+pub type AzPatternType = enum_AzPatternType;
+
 pub type enum_AzJoinStyle = c_uint;
 pub static AZ_JOIN_BEVEL: u32 = 0_u32;
 pub static AZ_JOIN_ROUND: u32 = 1_u32;
 pub static AZ_JOIN_MITER: u32 = 2_u32;
 pub static AZ_JOIN_MITER_OR_BEVEL: u32 = 3_u32;
 
+pub type AzJoinStyle = enum_AzJoinStyle;
+
 pub type enum_AzCapStyle = c_uint;
 pub static AZ_CAP_BUTT: u32 = 0_u32;
 pub static AZ_CAP_ROUND: u32 = 1_u32;
 pub static AZ_CAP_SQUARE: u32 = 2_u32;
 
+pub type AzCapStyle = enum_AzCapStyle;
+
 pub type enum_AzSamplingBounds = c_uint;
 pub static AZ_SAMPLING_UNBOUNDED: u32 = 0_u32;
 pub static AZ_SAMPLING_BOUNDED: u32 = 1_u32;
 
+pub type AzSamplingBounds = enum_AzSamplingBounds;
+
 pub type enum_AzSide = c_uint;
 pub static AZ_eSideTop: u32 = 0_u32;
 pub static AZ_eSideRight: u32 = 1_u32;
 pub static AZ_eSideBottom: u32 = 2_u32;
 pub static AZ_eSideLeft: u32 = 3_u32;
 
+pub type AzSide = enum_AzSide;
+
+pub type enum_AzResizeMethod = c_uint;
+pub static AZ_RESIZE_GOOD: u32 = 0_u32;
+pub static AZ_RESIZE_BETTER: u32 = 1_u32;
+pub static AZ_RESIZE_BEST: u32 = 2_u32;
+pub static AZ_RESIZE_BOX: u32 = 3_u32;
+pub static AZ_RESIZE_HAMMING1: u32 = 4_u32;
+pub static AZ_RESIZE_LANCZOS2: u32 = 5_u32;
+pub static AZ_RESIZE_LANCZOS3: u32 = 6_u32;
+
+pub type AzResizeMethod = enum_AzResizeMethod;
+
 #[repr(C)]
 #[deriving(Clone)]
 pub struct struct__AzColor {
@@ -162,14 +269,16 @@ pub struct struct__AzColor {
 pub type AzColor = struct__AzColor;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzGradientStop {
     pub offset: AzFloat,
     pub color: AzColor,
 }
 
-#[repr(C)]
 pub type AzGradientStop = struct__AzGradientStop;
 
+#[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzIntRect {
     pub x: int32_t,
     pub y: int32_t,
@@ -180,6 +289,7 @@ pub struct struct__AzIntRect {
 pub type AzIntRect = struct__AzIntRect;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzRect {
     pub x: AzFloat,
     pub y: AzFloat,
@@ -189,6 +299,8 @@ pub struct struct__AzRect {
 
 pub type AzRect = struct__AzRect;
 
+#[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzIntPoint {
     pub x: int32_t,
     pub y: int32_t,
@@ -197,6 +309,7 @@ pub struct struct__AzIntPoint {
 pub type AzIntPoint = struct__AzIntPoint;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzPoint {
     pub x: AzFloat,
     pub y: AzFloat,
@@ -205,6 +318,7 @@ pub struct struct__AzPoint {
 pub type AzPoint = struct__AzPoint;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzIntSize {
     pub width: int32_t,
     pub height: int32_t,
@@ -213,6 +327,7 @@ pub struct struct__AzIntSize {
 pub type AzIntSize = struct__AzIntSize;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzSize {
     pub width: AzFloat,
     pub height: AzFloat,
@@ -221,18 +336,20 @@ pub struct struct__AzSize {
 pub type AzSize = struct__AzSize;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzMatrix {
-    _11: AzFloat,
-    _12: AzFloat,
-    _21: AzFloat,
-    _22: AzFloat,
-    _31: AzFloat,
-    _32: AzFloat,
+    pub _11: AzFloat,
+    pub _12: AzFloat,
+    pub _21: AzFloat,
+    pub _22: AzFloat,
+    pub _31: AzFloat,
+    pub _32: AzFloat,
 }
 
 pub type AzMatrix = struct__AzMatrix;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzDrawOptions {
     pub mAlpha: AzFloat,
     pub fields: uint16_t,
@@ -241,10 +358,11 @@ pub struct struct__AzDrawOptions {
 pub type AzDrawOptions = struct__AzDrawOptions;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzStrokeOptions {
     pub mLineWidth: AzFloat,
     pub mMiterLimit: AzFloat,
-    pub mDashPattern: *mut AzFloat,
+    pub mDashPattern: *const AzFloat,
     pub mDashLength: size_t,
     pub mDashOffset: AzFloat,
     pub fields: uint8_t,
@@ -253,32 +371,33 @@ pub struct struct__AzStrokeOptions {
 pub type AzStrokeOptions = struct__AzStrokeOptions;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzDrawSurfaceOptions {
     pub fields: uint32_t,
 }
 
-#[repr(C)]
 pub type AzDrawSurfaceOptions = struct__AzDrawSurfaceOptions;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzGlyph {
     pub mIndex: uint32_t,
     pub mPosition: AzPoint,
 }
 
-#[repr(C)]
 pub type AzGlyph = struct__AzGlyph;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzGlyphBuffer {
-    pub mGlyphs: *mut AzGlyph,
+    pub mGlyphs: *const AzGlyph,
     pub mNumGlyphs: uint32_t,
 }
 
-#[repr(C)]
 pub type AzGlyphBuffer = struct__AzGlyphBuffer;
 
 #[repr(C)]
+#[deriving(Clone)]
 pub struct struct__AzNativeFont {
     pub mType: enum_AzNativeFontType,
     pub mFont: *mut c_void,
@@ -286,60 +405,48 @@ pub struct struct__AzNativeFont {
 
 pub type AzNativeFont = struct__AzNativeFont;
 
-pub type AzGradientStopsRef = *mut c_void;
-
-pub type AzSkiaSharedGLContextRef = *mut c_void;
-
-pub type AzSkiaSharedGrGLContextRef = *mut c_void;
-
-pub type AzSkiaGrContextRef = *mut c_void;
-
-pub type AzSkiaGrGLSharedSurfaceRef = *mut c_void;
-
-pub type AzDrawTargetRef = *mut c_void;
-
-pub type AzPatternRef = *mut c_void;
-
-pub type AzColorPatternRef = *mut c_void;
-
-pub type AzLinearGradientPatternRef = *mut c_void;
-
-pub type AzScaledFontRef = *mut c_void;
-
-pub type AzGlyphRenderingOptionsRef = *mut c_void;
-
-pub type AzSourceSurfaceRef = *mut c_void;
+#[repr(C)]
+#[deriving(Clone)]
+pub struct struct__AzFontMetrics {
+    pub mAscent: AzFloat,
+    pub mDescent: AzFloat,
+    pub mLeading: AzFloat,
+    pub mXHeight: AzFloat,
+    pub mUnderlinePosition: AzFloat,
+    pub mUnderlineThickness: AzFloat,
+}
 
-pub type AzDataSourceSurfaceRef = *mut c_void;
+pub type AzFontMetrics = struct__AzFontMetrics;
 
 #[repr(C)]
-pub type AzDrawSurfaceOptionsRef = *mut AzDrawSurfaceOptions;
-
-pub type AzGLContext = *mut c_void;
+#[deriving(Clone)]
+pub struct struct__AzRectCornerRadii {
+    pub mTopLeft: AzSize,
+    pub mTopRight: AzSize,
+    pub mBottomRight: AzSize,
+    pub mBottomLeft: AzSize,
+}
 
-pub type AzSkiaGrGLSharedContextRef = *mut c_void;
+pub type AzRectCornerRadii = struct__AzRectCornerRadii;
 
-pub type AzGLContextMetadataRef = *mut c_void;
+pub type AzGLContext = *mut c_void;
 
 pub type AzGLNativeContextRef = *mut c_void;
 
-pub type AzPathRef = *mut c_void;
-
-pub type AzPathBuilderRef = *mut c_void;
+pub type AzFontDataReleaseFunc = extern "C" fn(aClosure: *mut c_void);
 
-pub type AzExtendMode = i32;
+pub type AzFontOptions = c_void;
 
 #[link(name = "azure")]
 extern {
 
-pub fn AzSanityCheck(/* FIXME: variadic function */);
+pub fn AzSanityCheck();
 
-pub fn AzCreateColorPattern(aColor: *mut AzColor) -> AzColorPatternRef;
+pub fn AzGetLayoutEntries(aCount: *mut uint32_t) -> *const AzLayoutEntry;
 
-pub fn AzReleasePattern(aPattern: AzPatternRef);
+pub fn AzCreateColorPattern(aColor: *mut AzColor) -> AzColorPatternRef;
 
-pub fn AzCreateSkiaSharedGLContext(aNativeContext: AzGLNativeContextRef, aSize: *mut AzIntSize)
-                                   -> AzSkiaSharedGLContextRef;
+pub fn AzCreateSkiaSharedGLContext(aNativeContext: AzGLNativeContextRef, aSize: *mut AzIntSize) -> AzSkiaSharedGLContextRef;
 
 pub fn AzRetainSkiaSharedGLContext(aGLContext: AzSkiaSharedGLContextRef);
 
@@ -347,20 +454,32 @@ pub fn AzReleaseSkiaSharedGLContext(aGLContext: AzSkiaSharedGLContextRef);
 
 pub fn AzSkiaSharedGLContextGetFBOID(aGLContext: AzSkiaSharedGLContextRef) -> c_uint;
 
-pub fn AzSkiaSharedGLContextStealSurface(aGLContext: AzSkiaSharedGLContextRef) -> AzSkiaGrGLSharedSurfaceRef;
-
 pub fn AzSkiaSharedGLContextGetGrContext(aGLContext: AzSkiaSharedGLContextRef) -> AzSkiaGrContextRef;
 
 pub fn AzSkiaSharedGLContextMakeCurrent(aGLContext: AzSkiaSharedGLContextRef);
 
 pub fn AzSkiaSharedGLContextFlush(aGLContext: AzSkiaSharedGLContextRef);
 
+pub fn AzSkiaSharedGLContextStealSurface(aGLContext: AzSkiaSharedGLContextRef) -> AzSkiaGrGLSharedSurfaceRef;
+
+pub fn AzCPUSupportsSSE2() -> bool;
+
+pub fn AzSSE2PathsCompiled() -> bool;
+
+pub fn AzHasSSE2() -> bool;
+
+pub fn AzSetSSE2Disabled(aDisabled: bool);
+
 pub fn AzCreateDrawTarget(aBackend: AzBackendType, aSize: *mut AzIntSize, aFormat: AzSurfaceFormat) -> AzDrawTargetRef;
 
-pub fn AzCreateDrawTargetForData(aBackend: AzBackendType, aData: *mut c_uchar, aSize: *mut AzIntSize, aStride: i32, aFormat: AzSurfaceFormat) -> AzDrawTargetRef;
+pub fn AzCreateDrawTargetForData(aBackend: AzBackendType, aData: *mut c_uchar, aSize: *mut AzIntSize, aStride: int32_t, aFormat: AzSurfaceFormat) -> AzDrawTargetRef;
+
+pub fn AzCreateDualDrawTarget(aTargetA: AzDrawTargetRef, aTargetB: AzDrawTargetRef) -> AzDrawTargetRef;
 
 pub fn AzCreateSkiaDrawTargetForFBO(aGLContext: AzSkiaSharedGLContextRef, aSize: *mut AzIntSize, aFormat: AzSurfaceFormat) -> AzDrawTargetRef;
 
+pub fn AzCreateDrawTargetForCairoSurface(aSurface: AzCairoSurfaceRef, aSize: *mut AzIntSize) -> AzDrawTargetRef;
+
 pub fn AzRetainDrawTarget(aTarget: AzDrawTargetRef);
 
 pub fn AzReleaseDrawTarget(aTarget: AzDrawTargetRef);
@@ -369,18 +488,35 @@ pub fn AzDrawTargetGetSize(aDrawTarget: AzDrawTargetRef) -> AzIntSize;
 
 pub fn AzDrawTargetFlush(aDrawTarget: AzDrawTargetRef);
 
+pub fn AzDrawTargetGetFormat(aDrawTarget: AzDrawTargetRef) -> AzSurfaceFormat;
+
+pub fn AzDrawTargetReadPixels(aDrawTarget: AzDrawTargetRef, aRect: *const AzIntRect, aData: *mut uint8_t, aStride: int32_t) -> bool;
+
+pub fn AzDrawTargetSnapshotRect(aDrawTarget: AzDrawTargetRef, aRect: *const AzIntRect) -> AzDataSourceSurfaceRef;
+
 pub fn AzDrawTargetClearRect(aDrawTarget: AzDrawTargetRef, aRect: *mut AzRect);
 
-pub fn AzDrawTargetFillRect(aDrawTarget: AzDrawTargetRef,
-                            aRect: *mut AzRect,
-                            aPattern: AzPatternRef,
-                            aDrawOptions: *mut AzDrawOptions);
+pub fn AzDrawTargetFill(aDrawTarget: AzDrawTargetRef, aPath: AzPathRef, aPattern: AzPatternRef, aDrawOptions: *mut AzDrawOptions);
+
+pub fn AzDrawTargetFillRect(aDrawTarget: AzDrawTargetRef, aRect: *mut AzRect, aPattern: AzPatternRef, aDrawOptions: *mut AzDrawOptions);
 
 pub fn AzDrawTargetStrokeRect(aDrawTarget: AzDrawTargetRef, aRect: *mut AzRect, aPattern: AzPatternRef, aStrokeOptions: *mut AzStrokeOptions, aDrawOptions: *mut AzDrawOptions);
 
 pub fn AzDrawTargetStrokeLine(aDrawTarget: AzDrawTargetRef, aStart: *mut AzPoint, aEnd: *mut AzPoint, aPattern: AzPatternRef, aStrokeOptions: *mut AzStrokeOptions, aDrawOptions: *mut AzDrawOptions);
 
-pub fn AzDrawTargetFill(aDrawTarget: AzDrawTargetRef, aPath: AzPathRef, aPattern: AzPatternRef, aOptions: *mut AzDrawOptions);
+pub fn AzDrawTargetFillGlyphs(aDrawTarget: AzDrawTargetRef, aFont: AzScaledFontRef, aGlyphBuffer: *mut AzGlyphBuffer, aPattern: AzPatternRef, aOptions: *mut AzDrawOptions, aRenderingOptions: AzGlyphRenderingOptionsRef);
+
+pub fn AzDrawTargetDrawSurface(aDrawTarget: AzDrawTargetRef, aSurface: AzSourceSurfaceRef, aDest: *mut AzRect, aSource: *mut AzRect, aSurfOptions: *mut AzDrawSurfaceOptions, aOptions: *mut AzDrawOptions);
+
+pub fn AzDrawTargetDrawSurfaceWithShadow(aDrawTarget: AzDrawTargetRef, aSurface: AzSourceSurfaceRef, aPoint: *const AzPoint, aColor: *const AzColor, aOffset: *const AzPoint, aSigma: AzFloat, aOperator: AzCompositionOp);
+
+pub fn AzDrawTargetDrawBoxShadow(aDrawTarget: AzDrawTargetRef, aRect: *const AzRect, aRadii: *const AzRectCornerRadii, aColor: *const AzColor, aOffset: *const AzPoint, aSpread: AzFloat, aBlurSigma: AzFloat, aInset: bool, aClip: *const AzRect, aDrawOptions: *mut AzDrawOptions);
+
+pub fn AzDrawTargetGetSnapshot(aDrawTarget: AzDrawTargetRef) -> AzSourceSurfaceRef;
+
+pub fn AzDrawTargetCreateSourceSurfaceFromData(aDrawTarget: AzDrawTargetRef, aData: *const c_uchar, aSize: *mut AzIntSize, aStride: int32_t, aFormat: AzSurfaceFormat) -> AzSourceSurfaceRef;
+
+pub fn AzDrawTargetCreateGradientStops(aDrawTarget: AzDrawTargetRef, aStops: *const AzGradientStop, aNumStops: uint32_t, aExtendMode: AzExtendMode) -> AzGradientStopsRef;
 
 pub fn AzDrawTargetPushClip(aDrawTarget: AzDrawTargetRef, aPath: AzPathRef);
 
@@ -388,33 +524,21 @@ pub fn AzDrawTargetPushClipRect(aDrawTarget: AzDrawTargetRef, aRect: *const AzRe
 
 pub fn AzDrawTargetPopClip(aDrawTarget: AzDrawTargetRef);
 
-pub fn AzDrawTargetFillGlyphs(aDrawTarget: AzDrawTargetRef, aFont: AzScaledFontRef, aGlyphBuffer: *mut AzGlyphBuffer, aPattern: AzPatternRef, aOptions: *mut AzDrawOptions, aRenderingOptions: AzGlyphRenderingOptionsRef);
+pub fn AzDrawTargetSetTransform(aDrawTarget: AzDrawTargetRef, aTransform: *mut AzMatrix);
 
-pub fn AzDrawTargetDrawSurface(aDrawTarget: AzDrawTargetRef, aSurface: AzSourceSurfaceRef, aDest: *mut AzRect, aSource: *mut AzRect, aSurfOptions: AzDrawSurfaceOptionsRef, aOptions: *mut AzDrawOptions);
+pub fn AzDrawTargetGetTransform(aDrawTarget: AzDrawTargetRef) -> AzMatrix;
 
-pub fn AzDrawTargetDrawSurfaceWithShadow(aDrawTarget: AzDrawTargetRef,
-                                         aSurface: AzSourceSurfaceRef,
-                                         aDest: *const AzPoint,
-                                         aColor: *const AzColor,
-                                         aOffset: *const AzPoint,
-                                         aSigma: AzFloat,
-                                         aOperator: AzCompositionOp);
+pub fn AzDrawTargetConcatTransform(aDrawTarget: AzDrawTargetRef, aTransform: *const AzMatrix);
 
-pub fn AzDrawTargetGetSnapshot(aDrawTarget: AzDrawTargetRef) -> AzSourceSurfaceRef;
+pub fn AzMatrixRotation(aAngle: AzFloat) -> AzMatrix;
+
+pub fn AzMatrixInvert(aMatrix: *mut AzMatrix) -> bool;
 
-pub fn AzDrawTargetCreateSourceSurfaceFromData(aDrawTarget: AzDrawTargetRef, aData: *const u8, aSize: *mut AzIntSize, aStride: i32, aFormat: AzSurfaceFormat) -> AzSourceSurfaceRef;
+pub fn AzMatrixTransformBounds(aMatrix: *const AzMatrix, aRect: *const AzRect) -> AzRect;
 
-pub fn AzDrawTargetCreateGradientStops(aDrawTarget: AzDrawTargetRef,
-                                       aStops: *const AzGradientStop,
-                                       aNumStops: u32,
-                                       aExtendMode: AzExtendMode)
-                                       -> AzGradientStopsRef;
+pub fn AzMatrixIsIdentity(aMatrix: *const AzMatrix) -> bool;
 
-pub fn AzCreateLinearGradientPattern(aBegin: *const AzPoint,
-                                     aEnd: *const AzPoint,
-                                     aStops: AzGradientStopsRef,
-                                     aMatrix: *const AzMatrix)
-                                     -> AzLinearGradientPatternRef;
+pub fn AzMatrixIsRectilinear(aMatrix: *const AzMatrix) -> bool;
 
 pub fn AzReleaseSourceSurface(aSurface: AzSourceSurfaceRef);
 
@@ -424,43 +548,80 @@ pub fn AzSourceSurfaceGetFormat(aSurface: AzSourceSurfaceRef) -> AzSurfaceFormat
 
 pub fn AzSourceSurfaceGetDataSurface(aSurface: AzSourceSurfaceRef) -> AzDataSourceSurfaceRef;
 
-pub fn AzDataSourceSurfaceGetData(aSurface: AzDataSourceSurfaceRef) -> *mut u8;
+pub fn AzDataSourceSurfaceGetData(aSurface: AzDataSourceSurfaceRef) -> *mut uint8_t;
 
-pub fn AzDataSourceSurfaceGetStride(aSurface: AzDataSourceSurfaceRef) -> i32;
+pub fn AzDataSourceSurfaceGetStride(aSurface: AzDataSourceSurfaceRef) -> int32_t;
 
-pub fn AzCreateScaledFontForNativeFont(aNativeFont: *mut AzNativeFont, aSize: AzFloat) -> AzScaledFontRef;
+pub fn AzCreateDataSourceSurface(aSize: *const AzIntSize, aFormat: AzSurfaceFormat) -> AzDataSourceSurfaceRef;
 
-pub fn AzReleaseScaledFont(aFont: AzScaledFontRef);
-
-pub fn AzDrawTargetSetTransform(aDrawTarget: AzDrawTargetRef, aTransform: *mut AzMatrix);
+pub fn AzCalculateBlurRadius(aStandardDeviation: *const AzPoint) -> AzIntSize;
 
-pub fn AzCreateFontOptionsForData(aFontData: *const u8, aFontDataSize: u32) -> *mut AzFontOptions;
+pub fn AzBlurDataSourceSurface(aSurface: AzDataSourceSurfaceRef, aSpreadRadius: *const AzIntSize, aBlurRadius: *const AzIntSize, aDirtyRect: *const AzRect, aSkipRect: *const AzRect, aResultRect: *mut AzIntRect) -> AzDataSourceSurfaceRef;
 
-pub fn AzCreateFontOptionsForName(aName: *const c_char, aStyle: enum_AzFontStyle) -> *mut AzFontOptions;
+pub fn AzScaleDataSourceSurface(aSurface: AzDataSourceSurfaceRef, aSize: *const AzIntSize, aMethod: AzResizeMethod) -> AzDataSourceSurfaceRef;
 
-pub fn AzDestroyFontOptions(aOptions: *mut AzFontOptions);
-
-pub fn AzSkiaGetCurrentGLContext() -> AzGLContext;
+pub fn AzHalfScaleDataSourceSurface(aSurface: AzDataSourceSurfaceRef, aSize: *const AzIntSize) -> AzDataSourceSurfaceRef;
 
 pub fn AzCreatePathBuilder(aDrawTarget: AzDrawTargetRef) -> AzPathBuilderRef;
 
 pub fn AzReleasePathBuilder(aPathBuilder: AzPathBuilderRef);
 
-pub fn AzPathBuilderMoveTo(aPathBuilder: AzPathBuilderRef, aPoint: *mut AzPoint);
+pub fn AzPathBuilderMoveTo(aPathBuilder: AzPathBuilderRef, aPoint: *const AzPoint);
 
-pub fn AzPathBuilderLineTo(aPathBuilder: AzPathBuilderRef, aPoint: *mut AzPoint);
+pub fn AzPathBuilderLineTo(aPathBuilder: AzPathBuilderRef, aPoint: *const AzPoint);
 
-pub fn AzPathBuilderArc(aPathBuilder: AzPathBuilderRef,
-                        aOrigin: *const AzPoint,
-                        aRadius: AzFloat,
-                        aStartAngle: AzFloat,
-                        aEndAngle: AzFloat,
-                        aAntiClockwise: bool);
+pub fn AzPathBuilderArc(aPathBuilder: AzPathBuilderRef, aOrigin: *const AzPoint, aRadius: AzFloat, aStartAngle: AzFloat, aEndAngle: AzFloat, aAntiClockwise: bool);
 
 pub fn AzPathBuilderFinish(aPathBuilder: AzPathBuilderRef) -> AzPathRef;
 
 pub fn AzReleasePath(aPath: AzPathRef);
 
-pub fn AzReleaseGradientStops(aFont: AzScaledFontRef);
+pub fn AzPathGetBounds(aPath: AzPathRef, aTransform: *const AzMatrix) -> AzRect;
+
+pub fn AzCreateLinearGradientPattern(aBegin: *const AzPoint, aEnd: *const AzPoint, aStops: AzGradientStopsRef, aMatrix: *const AzMatrix) -> AzLinearGradientPatternRef;
+
+pub fn AzReleasePattern(aPattern: AzPatternRef);
+
+pub fn AzReleaseGradientStops(aStops: AzGradientStopsRef);
+
+pub fn AzCreateSkiaGlyphRenderingOptions(aHinting: AzFontHinting, aAutohinter: bool, aSubpixelPositioning: bool, aLCDText: bool, aGamma: AzFloat, aContrast: AzFloat, aColorGlyphs: bool) -> AzGlyphRenderingOptionsRef;
+
+pub fn AzReleaseGlyphRenderingOptions(aOptions: AzGlyphRenderingOptionsRef);
+
+pub fn AzCreateScaledFontForNativeFont(aNativeFont: *mut AzNativeFont, aSize: AzFloat) -> AzScaledFontRef;
+
+pub fn AzCreateScaledFontForTrueTypeData(aData: *mut uint8_t, aSize: uint32_t, aFaceIndex: uint32_t, aGlyphSize: AzFloat, aType: AzFontType, aRelease: AzFontDataReleaseFunc, aClosure: *mut c_void) -> AzScaledFontRef;
+
+pub fn AzCreateScaledFontWithCairo(aNativeFont: *mut AzNativeFont, aSize: AzFloat, aScaledFont: AzCairoScaledFontRef) -> AzScaledFontRef;
+
+pub fn AzReleaseScaledFont(aFont: AzScaledFontRef);
+
+pub fn AzScaledFontGetPathForGlyphs(aFont: AzScaledFontRef, aGlyphBuffer: *const AzGlyphBuffer, aDrawTarget: AzDrawTargetRef) -> AzPathRef;
+
+pub fn AzScaledFontGetMetrics(aFont: AzScaledFontRef, aMetrics: *mut AzFontMetrics) -> bool;
+
+pub fn AzScaledFontGetGlyphMetrics(aFont: AzScaledFontRef, aGlyph: uint32_t, aAdvance: *mut AzFloat, aBounds: *mut AzRect) -> bool;
+
+pub fn AzScaledFontGetGlyphIndex(aFont: AzScaledFontRef, aCharacter: uint32_t) -> uint32_t;
+
+pub fn AzScaledFontSetSyntheticStyle(aFont: AzScaledFontRef, aBold: bool, aOblique: bool);
+
+pub fn AzScaledFontCopyGlyphsToBuilder(aFont: AzScaledFontRef, aGlyphBuffer: *const AzGlyphBuffer, aPathBuilder: AzPathBuilderRef) -> bool;
+
+pub fn AzCreateFontOptionsForName(aName: *const c_char, aStyle: AzFontStyle) -> *mut AzFontOptions;
+
+pub fn AzCreateFontOptionsForData(aFontData: *const uint8_t, aFontDataSize: uint32_t) -> *mut AzFontOptions;
+
+pub fn AzCreateFontOptionsForDescriptor(aFamily: *const c_char, aWeight: uint16_t, aSlant: AzFontSlant, aStretch: uint16_t) -> *mut AzFontOptions;
+
+pub fn AzCreateFontOptionsForFreetypeFace(aFace: *mut c_void) -> *mut AzFontOptions;
+
+pub fn AzDestroyFontOptions(aOptions: *mut AzFontOptions);
+
+pub fn AzCreateCairoScaledFontForFreetypeFace(aFace: *mut c_void, aSize: AzFloat) -> AzCairoScaledFontRef;
+
+pub fn AzReleaseCairoScaledFont(aFont: AzCairoScaledFontRef);
+
+pub fn AzSkiaGetCurrentGLContext() -> AzGLContext;
 
 }
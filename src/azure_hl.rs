@@ -8,21 +8,27 @@ use azure::{AZ_CAP_BUTT, AZ_JOIN_MITER_OR_BEVEL};
 use azure::{AzPoint, AzRect, AzFloat, AzIntSize, AzColor, AzColorPatternRef, AzGradientStopsRef};
 use azure::{AzStrokeOptions, AzDrawOptions, AzSurfaceFormat, AzFilter, AzDrawSurfaceOptions};
 use azure::{AzBackendType, AzDrawTargetRef, AzSourceSurfaceRef, AzDataSourceSurfaceRef};
-use azure::{AzScaledFontRef, AzGlyphRenderingOptionsRef, AzExtendMode, AzGradientStop};
-use azure::{AzCompositionOp};
-use azure::{struct__AzColor, struct__AzGlyphBuffer};
+use azure::{AzGlyphRenderingOptionsRef, AzExtendMode, AzGradientStop};
+use azure::{AzCompositionOp, AZ_AA_DEFAULT};
+use azure::{AzGlyph, struct__AzColor, struct__AzGlyph, struct__AzGlyphBuffer};
 use azure::{struct__AzDrawOptions, struct__AzDrawSurfaceOptions, struct__AzIntSize};
 use azure::{struct__AzPoint, struct__AzRect, struct__AzStrokeOptions};
 use azure::{AzGLContext, AzSkiaSharedGLContextRef};
 use azure::{AzCreateColorPattern, AzCreateDrawTarget, AzCreateDrawTargetForData};
+use azure::{AzCreateDualDrawTarget};
+#[cfg(feature = "cairo")]
+use azure::{AzCreateDrawTargetForCairoSurface, AzCairoSurfaceRef};
 use azure::{AzDataSourceSurfaceGetData, AzDataSourceSurfaceGetStride};
-use azure::{AzDrawTargetClearRect};
+use azure::{AzCreateDataSourceSurface, AzCalculateBlurRadius, AzBlurDataSourceSurface};
+use azure::{struct__AzIntRect, AzRectCornerRadii, AzDrawTargetDrawBoxShadow};
+use azure::{AzDrawTargetClearRect, AzDrawTargetGetFormat, AzDrawTargetReadPixels};
+use azure::{AzDrawTargetSnapshotRect, AzIntRect};
 use azure::{AzDrawTargetCreateSourceSurfaceFromData, AzCreateSkiaSharedGLContext};
 use azure::{AzReleaseSkiaSharedGLContext, AzRetainSkiaSharedGLContext};
 use azure::{AzDrawTargetDrawSurface, AzDrawTargetFillRect, AzDrawTargetFlush};
 use azure::{AzDrawTargetGetSize, AzDrawTargetGetSnapshot, AzDrawTargetSetTransform};
 use azure::{AzDrawTargetStrokeLine, AzDrawTargetStrokeRect, AzDrawTargetFillGlyphs};
-use azure::{AzDrawTargetCreateGradientStops};
+use azure::{AzDrawTargetCreateGradientStops, AzReleaseGlyphRenderingOptions};
 use azure::{AzReleaseDrawTarget, AzReleasePattern, AzReleaseGradientStops};
 use azure::{AzReleaseSourceSurface, AzRetainDrawTarget};
 use azure::{AzSourceSurfaceGetDataSurface, AzSourceSurfaceGetFormat};
@@ -35,8 +41,16 @@ use azure::{AzDrawTargetFill, AzPathRef, AzReleasePath, AzDrawTargetPushClip, Az
 use azure::{AzGLNativeContextRef, AzLinearGradientPatternRef, AzMatrix, AzPatternRef};
 use azure::{AzCreateLinearGradientPattern, AzDrawTargetPushClipRect};
 use azure::{AzDrawTargetDrawSurfaceWithShadow};
+use azure::{AzFontHinting, AzCreateSkiaGlyphRenderingOptions};
+use azure::{AzResizeMethod, AzScaleDataSourceSurface, AzHalfScaleDataSourceSurface};
+use azure::{AzCPUSupportsSSE2, AzSSE2PathsCompiled, AzHasSSE2, AzSetSSE2Disabled};
+use azure::{struct__AzMatrix, AzDrawTargetGetTransform, AzDrawTargetConcatTransform};
+use azure::{AzMatrixRotation, AzMatrixInvert, AzMatrixTransformBounds, AzMatrixIsIdentity};
+use azure::{AzMatrixIsRectilinear, AzPathGetBounds};
 
-use sync::Arc;
+use scaled_font::ScaledFont;
+
+use sync::{Arc, Mutex};
 use geom::matrix2d::Matrix2D;
 use geom::point::Point2D;
 use geom::rect::Rect;
@@ -44,7 +58,11 @@ use geom::size::Size2D;
 use layers::platform::surface::NativePaintingGraphicsContext;
 use libc::types::common::c99::{uint8_t, uint16_t};
 use libc::size_t;
+use png;
+use std::cmp;
+use std::io::{File, IoError, IoResult, InvalidInput, OtherIoError, TempDir};
 use std::mem;
+use std::path::Path as FilePath;
 use std::ptr;
 use std::slice;
 
@@ -66,6 +84,41 @@ impl AsAzureRect for Rect<AzFloat> {
     }
 }
 
+pub trait AsAzureMatrix {
+    fn as_azure_matrix(&self) -> AzMatrix;
+}
+
+impl AsAzureMatrix for Matrix2D<AzFloat> {
+    fn as_azure_matrix(&self) -> AzMatrix {
+        Matrix::from_matrix2d(self).as_azure_matrix()
+    }
+}
+
+impl AsAzureMatrix for Matrix {
+    fn as_azure_matrix(&self) -> AzMatrix {
+        struct__AzMatrix {
+            _11: self.m11, _12: self.m12,
+            _21: self.m21, _22: self.m22,
+            _31: self.m31, _32: self.m32
+        }
+    }
+}
+
+pub trait AsAzureIntRect {
+    fn as_azure_int_rect(&self) -> AzIntRect;
+}
+
+impl AsAzureIntRect for Rect<i32> {
+    fn as_azure_int_rect(&self) -> AzIntRect {
+        struct__AzIntRect {
+            x: self.origin.x,
+            y: self.origin.y,
+            width: self.size.width,
+            height: self.size.height
+        }
+    }
+}
+
 pub trait AsAzureIntSize {
     fn as_azure_int_size(&self) -> AzIntSize;
 }
@@ -92,6 +145,7 @@ impl AsAzurePoint for Point2D<AzFloat> {
     }
 }
 
+#[repr(C)]
 #[deriving(Clone)]
 pub struct Color {
     pub r: AzFloat,
@@ -110,6 +164,275 @@ impl Color {
     }
 }
 
+/// The elliptical radii of the corners of a rounded rectangle.
+#[repr(C)]
+#[deriving(Clone)]
+pub struct CornerRadii {
+    pub top_left: Size2D<AzFloat>,
+    pub top_right: Size2D<AzFloat>,
+    pub bottom_right: Size2D<AzFloat>,
+    pub bottom_left: Size2D<AzFloat>,
+}
+
+impl CornerRadii {
+    pub fn new(top_left: Size2D<AzFloat>,
+               top_right: Size2D<AzFloat>,
+               bottom_right: Size2D<AzFloat>,
+               bottom_left: Size2D<AzFloat>)
+               -> CornerRadii {
+        CornerRadii {
+            top_left: top_left,
+            top_right: top_right,
+            bottom_right: bottom_right,
+            bottom_left: bottom_left,
+        }
+    }
+
+    /// Circular corners of the same radius.
+    pub fn uniform(radius: AzFloat) -> CornerRadii {
+        let size = Size2D(radius, radius);
+        CornerRadii::new(size, size, size, size)
+    }
+}
+
+/// A 2D affine transform, with the same layout and conventions as libazure's `Matrix`. Points
+/// are row vectors, so `(x, y)` maps to `(x * m11 + y * m21 + m31, x * m12 + y * m22 + m32)`.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Matrix {
+    pub m11: AzFloat, pub m12: AzFloat,
+    pub m21: AzFloat, pub m22: AzFloat,
+    pub m31: AzFloat, pub m32: AzFloat,
+}
+
+impl Matrix {
+    pub fn new(m11: AzFloat, m12: AzFloat,
+               m21: AzFloat, m22: AzFloat,
+               m31: AzFloat, m32: AzFloat)
+               -> Matrix {
+        Matrix { m11: m11, m12: m12, m21: m21, m22: m22, m31: m31, m32: m32 }
+    }
+
+    pub fn identity() -> Matrix {
+        Matrix::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn translation(x: AzFloat, y: AzFloat) -> Matrix {
+        Matrix::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub fn scale(x: AzFloat, y: AzFloat) -> Matrix {
+        Matrix::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// A rotation by `angle` radians, clockwise in Azure's y-down space.
+    pub fn rotation(angle: AzFloat) -> Matrix {
+        unsafe {
+            Matrix::from_azure_matrix(&AzMatrixRotation(angle))
+        }
+    }
+
+    pub fn from_matrix2d(matrix: &Matrix2D<AzFloat>) -> Matrix {
+        Matrix::new(matrix.m11, matrix.m12, matrix.m21, matrix.m22, matrix.m31, matrix.m32)
+    }
+
+    pub fn to_matrix2d(&self) -> Matrix2D<AzFloat> {
+        Matrix2D::new(self.m11, self.m12, self.m21, self.m22, self.m31, self.m32)
+    }
+
+    fn from_azure_matrix(matrix: &AzMatrix) -> Matrix {
+        Matrix::new(matrix._11, matrix._12, matrix._21, matrix._22, matrix._31, matrix._32)
+    }
+
+    /// The transform that applies `self` and then `other`.
+    pub fn multiply(&self, other: &Matrix) -> Matrix {
+        Matrix::new(self.m11 * other.m11 + self.m12 * other.m21,
+                    self.m11 * other.m12 + self.m12 * other.m22,
+                    self.m21 * other.m11 + self.m22 * other.m21,
+                    self.m21 * other.m12 + self.m22 * other.m22,
+                    self.m31 * other.m11 + self.m32 * other.m21 + other.m31,
+                    self.m31 * other.m12 + self.m32 * other.m22 + other.m32)
+    }
+
+    /// Returns the inverse transform, or `None` if the matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix> {
+        let mut matrix = self.as_azure_matrix();
+        if unsafe { AzMatrixInvert(&mut matrix) } {
+            Some(Matrix::from_azure_matrix(&matrix))
+        } else {
+            None
+        }
+    }
+
+    pub fn transform_point(&self, point: &Point2D<AzFloat>) -> Point2D<AzFloat> {
+        Point2D(point.x * self.m11 + point.y * self.m21 + self.m31,
+                point.x * self.m12 + point.y * self.m22 + self.m32)
+    }
+
+    /// The smallest axis-aligned rectangle containing `rect` once transformed.
+    pub fn transform_bounds(&self, rect: &Rect<AzFloat>) -> Rect<AzFloat> {
+        let bounds = unsafe {
+            AzMatrixTransformBounds(&self.as_azure_matrix(), &rect.as_azure_rect())
+        };
+        Rect(Point2D(bounds.x, bounds.y), Size2D(bounds.width, bounds.height))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        unsafe {
+            AzMatrixIsIdentity(&self.as_azure_matrix())
+        }
+    }
+
+    /// Whether axis-aligned rectangles stay axis-aligned under the transform.
+    pub fn is_rectilinear(&self) -> bool {
+        unsafe {
+            AzMatrixIsRectilinear(&self.as_azure_matrix())
+        }
+    }
+}
+
+/// A glyph index into a font, positioned at a point in user space.
+#[deriving(Clone)]
+pub struct Glyph {
+    pub index: u32,
+    pub position: Point2D<AzFloat>,
+}
+
+impl Glyph {
+    pub fn new(index: u32, position: Point2D<AzFloat>) -> Glyph {
+        Glyph { index: index, position: position }
+    }
+
+    pub fn as_azure_glyph(&self) -> AzGlyph {
+        struct__AzGlyph {
+            mIndex: self.index,
+            mPosition: self.position.as_azure_point(),
+        }
+    }
+}
+
+/// Backend-specific options controlling how glyphs are rasterized.
+pub struct GlyphRenderingOptions {
+    pub azure_glyph_rendering_options: AzGlyphRenderingOptionsRef,
+}
+
+impl Drop for GlyphRenderingOptions {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseGlyphRenderingOptions(self.azure_glyph_rendering_options);
+        }
+    }
+}
+
+impl GlyphRenderingOptions {
+    /// Takes ownership of a reference to glyph rendering options created by libazure.
+    pub fn new(azure_glyph_rendering_options: AzGlyphRenderingOptionsRef)
+               -> GlyphRenderingOptions {
+        if azure_glyph_rendering_options.is_null() {
+            panic!("null azure glyph rendering options");
+        }
+        GlyphRenderingOptions {
+            azure_glyph_rendering_options: azure_glyph_rendering_options,
+        }
+    }
+}
+
+/// How strongly glyph outlines are fitted to the pixel grid.
+#[repr(i32)]
+#[deriving(Clone, PartialEq)]
+pub enum FontHinting {
+    NoHinting = 0,
+    LightHinting = 1,
+    NormalHinting = 2,
+    FullHinting = 3,
+}
+
+impl FontHinting {
+    fn as_azure_font_hinting(self) -> AzFontHinting {
+        self as AzFontHinting
+    }
+}
+
+/// Builds Skia glyph rendering options. Options left unset keep Skia's usual text rendering.
+#[deriving(Clone)]
+pub struct GlyphRenderingOptionsBuilder {
+    hinting: FontHinting,
+    autohinter: bool,
+    subpixel_positioning: bool,
+    lcd_text: bool,
+    gamma: AzFloat,
+    contrast: AzFloat,
+    color_glyphs: bool,
+}
+
+impl GlyphRenderingOptionsBuilder {
+    pub fn new() -> GlyphRenderingOptionsBuilder {
+        GlyphRenderingOptionsBuilder {
+            hinting: NormalHinting,
+            autohinter: false,
+            subpixel_positioning: true,
+            lcd_text: false,
+            gamma: 1.0,
+            contrast: 0.0,
+            color_glyphs: false,
+        }
+    }
+
+    pub fn hinting(mut self, hinting: FontHinting) -> GlyphRenderingOptionsBuilder {
+        self.hinting = hinting;
+        self
+    }
+
+    /// Uses FreeType's autohinter instead of the font's own hinting instructions.
+    pub fn autohinter(mut self, autohinter: bool) -> GlyphRenderingOptionsBuilder {
+        self.autohinter = autohinter;
+        self
+    }
+
+    pub fn subpixel_positioning(mut self, subpixel_positioning: bool)
+                                -> GlyphRenderingOptionsBuilder {
+        self.subpixel_positioning = subpixel_positioning;
+        self
+    }
+
+    /// Renders glyphs with subpixel (LCD) antialiasing.
+    pub fn lcd_text(mut self, lcd_text: bool) -> GlyphRenderingOptionsBuilder {
+        self.lcd_text = lcd_text;
+        self
+    }
+
+    /// Gamma applied to glyph coverage. Ignored for LCD text.
+    pub fn gamma(mut self, gamma: AzFloat) -> GlyphRenderingOptionsBuilder {
+        self.gamma = gamma;
+        self
+    }
+
+    /// Contrast applied to glyph coverage, from 0 to 1. Ignored for LCD text.
+    pub fn contrast(mut self, contrast: AzFloat) -> GlyphRenderingOptionsBuilder {
+        self.contrast = contrast;
+        self
+    }
+
+    /// Draws color bitmap glyphs, such as emoji, in their own colours instead of filling
+    /// their shape with the pattern.
+    pub fn color_glyphs(mut self, color_glyphs: bool) -> GlyphRenderingOptionsBuilder {
+        self.color_glyphs = color_glyphs;
+        self
+    }
+
+    pub fn finish(self) -> GlyphRenderingOptions {
+        unsafe {
+            GlyphRenderingOptions::new(
+                AzCreateSkiaGlyphRenderingOptions(self.hinting.as_azure_font_hinting(),
+                                                  self.autohinter,
+                                                  self.subpixel_positioning,
+                                                  self.lcd_text,
+                                                  self.gamma,
+                                                  self.contrast,
+                                                  self.color_glyphs))
+        }
+    }
+}
+
 
 // FIXME: Should have a class hierarchy here starting with Pattern.
 pub struct ColorPattern {
@@ -167,7 +490,7 @@ pub enum CompositionOp {
 pub struct StrokeOptions {
     pub line_width: AzFloat,
     pub miter_limit: AzFloat,
-    pub mDashPattern: *mut AzFloat,
+    pub mDashPattern: *const AzFloat,
     pub mDashLength: size_t,
     pub fields: uint8_t
 }
@@ -177,7 +500,7 @@ impl StrokeOptions {
         StrokeOptions {
             line_width: line_width,
             miter_limit: miter_limit,
-            mDashPattern: ptr::null_mut(),
+            mDashPattern: ptr::null(),
             mDashLength: 0,
             fields: AZ_CAP_BUTT as u8 << 4 | AZ_JOIN_MITER_OR_BEVEL as u8
         }
@@ -248,7 +571,14 @@ pub enum SurfaceFormat {
     B8G8R8A8,
     B8G8R8X8,
     R5G6B5,
-    A8
+    A8,
+    // The formats below can be drawn from but not into. Draw targets asked for them use
+    // B8G8R8A8 or B8G8R8X8 instead, and surfaces created from their data by a draw target are
+    // converted to those formats.
+    R8G8B8A8,
+    R8G8B8X8,
+    /// Premultiplied, with a native-endian `u16` per channel.
+    R16G16B16A16,
 }
 
 impl SurfaceFormat {
@@ -262,9 +592,21 @@ impl SurfaceFormat {
             1 => B8G8R8X8,
             2 => R5G6B5,
             3 => A8,
+            4 => R8G8B8A8,
+            5 => R8G8B8X8,
+            6 => R16G16B16A16,
             _ => panic!("SurfaceFormat::new(): unknown Azure surface format")
         }
     }
+
+    pub fn bytes_per_pixel(self) -> uint {
+        match self {
+            A8 => 1,
+            R5G6B5 => 2,
+            B8G8R8A8 | B8G8R8X8 | R8G8B8A8 | R8G8B8X8 => 4,
+            R16G16B16A16 => 8,
+        }
+    }
 }
 
 pub enum Filter {
@@ -327,7 +669,92 @@ impl BackendType {
 pub struct DrawTarget {
     pub azure_draw_target: AzDrawTargetRef,
     pub data: Option<Arc<Vec<u8>>>,
-    pub skia_context: Option<AzSkiaSharedGLContextRef>
+    pub skia_context: Option<AzSkiaSharedGLContextRef>,
+    /// For dual draw targets, the two targets that commands are mirrored into. These are kept
+    /// here so that any backing data they own outlives the dual target.
+    pub dual_targets: Option<(Box<DrawTarget>, Box<DrawTarget>)>,
+    /// The pushed clips, global alpha, composition op and saved states. Clones share it, since
+    /// they draw into the same Azure draw target.
+    state: Arc<Mutex<DrawTargetState>>,
+}
+
+struct DrawTargetState {
+    clips: Vec<Clip>,
+    /// The id to give the next clip pushed.
+    next_clip_id: uint,
+    global_alpha: AzFloat,
+    composition_op: CompositionOp,
+    saved: Vec<SavedState>,
+}
+
+impl DrawTargetState {
+    fn new() -> DrawTargetState {
+        DrawTargetState {
+            clips: Vec::new(),
+            next_clip_id: 0,
+            global_alpha: 1.0,
+            composition_op: OverOp,
+            saved: Vec::new(),
+        }
+    }
+}
+
+/// A pushed clip.
+struct Clip {
+    /// Identifies the clip to the guard it was pushed for, if any.
+    id: uint,
+    /// The device space bounds of the clip.
+    bounds: Rect<AzFloat>,
+}
+
+/// A state recorded by `DrawTarget::save`.
+struct SavedState {
+    transform: Matrix2D<AzFloat>,
+    clip_depth: uint,
+    global_alpha: AzFloat,
+    composition_op: CompositionOp,
+}
+
+/// Restores the draw target to the state it had when the guard was made, popping the clips
+/// pushed since then, along with any states saved and not restored. Returned by
+/// `DrawTarget::scoped_save`.
+pub struct StateGuard<'a> {
+    draw_target: &'a DrawTarget,
+    depth: uint,
+}
+
+/// Pops the clip it was returned for when dropped, along with any clips pushed after it and not
+/// popped. If a `restore` has already popped the clip, the guard leaves the clips pushed since
+/// alone. Returned by `DrawTarget::push_clip_scoped` and `DrawTarget::push_clip_rect_scoped`.
+pub struct ClipGuard<'a> {
+    draw_target: &'a DrawTarget,
+    id: uint,
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for ClipGuard<'a> {
+    fn drop(&mut self) {
+        let depth = self.draw_target.state.lock().clips.iter().position(|clip| clip.id == self.id);
+        match depth {
+            Some(depth) => {
+                while self.draw_target.clip_depth() > depth {
+                    self.draw_target.pop_clip();
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for StateGuard<'a> {
+    fn drop(&mut self) {
+        // Unlike `restore`, this mustn't panic, since it may run while unwinding from a panic.
+        // States that can't be fully restored are still popped.
+        while self.draw_target.save_depth() > self.depth {
+            let _ = self.draw_target.pop_saved_state();
+        }
+    }
 }
 
 impl Drop for DrawTarget {
@@ -362,7 +789,9 @@ impl DrawTarget {
         DrawTarget {
             azure_draw_target: azure_draw_target,
             data: None,
-            skia_context: None
+            skia_context: None,
+            dual_targets: None,
+            state: Arc::new(Mutex::new(DrawTargetState::new())),
         }
     }
 
@@ -386,7 +815,9 @@ impl DrawTarget {
         DrawTarget {
             azure_draw_target: azure_draw_target,
             data: Some(Arc::new(data)),
-            skia_context: None
+            skia_context: None,
+            dual_targets: None,
+            state: Arc::new(Mutex::new(DrawTargetState::new())),
         }
     }
 
@@ -411,7 +842,48 @@ impl DrawTarget {
         DrawTarget {
             azure_draw_target: azure_draw_target,
             data: None,
-            skia_context: Some(skia_context)
+            skia_context: Some(skia_context),
+            dual_targets: None,
+            state: Arc::new(Mutex::new(DrawTargetState::new())),
+        }
+    }
+
+    /// Creates a Cairo draw target wrapping the given `cairo_surface_t`. The draw target takes its
+    /// own reference to the surface.
+    #[cfg(feature = "cairo")]
+    pub fn new_with_cairo_surface(surface: AzCairoSurfaceRef, size: Size2D<i32>) -> DrawTarget {
+        let azure_draw_target = unsafe {
+            AzCreateDrawTargetForCairoSurface(surface, &mut size.as_azure_int_size())
+        };
+        if azure_draw_target.is_null() {
+            panic!("null azure draw target");
+        }
+        DrawTarget {
+            azure_draw_target: azure_draw_target,
+            data: None,
+            skia_context: None,
+            dual_targets: None,
+            state: Arc::new(Mutex::new(DrawTargetState::new())),
+        }
+    }
+
+    /// Creates a draw target that forwards every drawing command to both `target_a` and
+    /// `target_b`. This is useful for rendering onto black and white backgrounds at once in order
+    /// to compute per-component alpha. Snapshots of the returned target are dual surfaces; when
+    /// they are drawn into another dual target, each half is drawn into the corresponding target.
+    pub fn new_dual(target_a: &DrawTarget, target_b: &DrawTarget) -> DrawTarget {
+        let azure_draw_target = unsafe {
+            AzCreateDualDrawTarget(target_a.azure_draw_target, target_b.azure_draw_target)
+        };
+        if azure_draw_target.is_null() {
+            panic!("null azure draw target");
+        }
+        DrawTarget {
+            azure_draw_target: azure_draw_target,
+            data: None,
+            skia_context: None,
+            dual_targets: Some((box target_a.clone(), box target_b.clone())),
+            state: Arc::new(Mutex::new(DrawTargetState::new())),
         }
     }
 
@@ -429,7 +901,12 @@ impl DrawTarget {
                 None => None,
                 Some(ref arc) => Some(arc.clone())
             },
-            skia_context: self.skia_context
+            skia_context: self.skia_context,
+            dual_targets: match self.dual_targets {
+                None => None,
+                Some((ref a, ref b)) => Some((box a.clone(), box b.clone()))
+            },
+            state: self.state.clone(),
         }
     }
 
@@ -459,6 +936,12 @@ impl DrawTarget {
         }
     }
 
+    pub fn get_format(&self) -> SurfaceFormat {
+        unsafe {
+            SurfaceFormat::new(AzDrawTargetGetFormat(self.azure_draw_target))
+        }
+    }
+
     pub fn flush(&self) {
         unsafe {
             AzDrawTargetFlush(self.azure_draw_target);
@@ -475,12 +958,21 @@ impl DrawTarget {
         }
     }
 
-    pub fn fill(&self, path: &Path, pattern: &ColorPattern, draw_options: &DrawOptions) {
+    /// The options for a drawing command: `draw_options` if given, or else the global alpha and
+    /// composition op.
+    fn azure_draw_options(&self, draw_options: Option<&DrawOptions>) -> AzDrawOptions {
+        match draw_options {
+            Some(draw_options) => draw_options.as_azure_draw_options(),
+            None => self.draw_options().as_azure_draw_options(),
+        }
+    }
+
+    pub fn fill(&self, path: &Path, pattern: &ColorPattern, draw_options: Option<&DrawOptions>) {
         unsafe {
             AzDrawTargetFill(self.azure_draw_target,
                              path.azure_path,
                              pattern.azure_color_pattern,
-                             &mut draw_options.as_azure_draw_options());
+                             &mut self.azure_draw_options(draw_options));
         }
     }
 
@@ -488,18 +980,12 @@ impl DrawTarget {
                      rect: &Rect<AzFloat>,
                      pattern: PatternRef,
                      draw_options: Option<&DrawOptions>) {
-        let mut draw_options = draw_options.map(|draw_options| {
-            draw_options.as_azure_draw_options()
-        });
-        let draw_options = match draw_options {
-            None => ptr::null_mut(),
-            Some(ref mut draw_options) => draw_options as *mut AzDrawOptions
-        };
+        let mut draw_options = self.azure_draw_options(draw_options);
         unsafe {
             AzDrawTargetFillRect(self.azure_draw_target,
                                  &mut rect.as_azure_rect(),
                                  pattern.as_azure_pattern(),
-                                 draw_options);
+                                 &mut draw_options);
         }
     }
 
@@ -508,14 +994,14 @@ impl DrawTarget {
                    end: Point2D<AzFloat>,
                    pattern: &ColorPattern,
                    stroke_options: &StrokeOptions,
-                   draw_options: &DrawOptions) {
+                   draw_options: Option<&DrawOptions>) {
         unsafe {
             AzDrawTargetStrokeLine(self.azure_draw_target,
                                    &mut start.as_azure_point(),
                                    &mut end.as_azure_point(),
                                    pattern.azure_color_pattern,
                                    &mut stroke_options.as_azure_stroke_options(),
-                                   &mut draw_options.as_azure_draw_options());
+                                   &mut self.azure_draw_options(draw_options));
         }
     }
 
@@ -523,13 +1009,13 @@ impl DrawTarget {
                    rect: &Rect<AzFloat>,
                    pattern: &ColorPattern,
                    stroke_options: &StrokeOptions,
-                   draw_options: &DrawOptions) {
+                   draw_options: Option<&DrawOptions>) {
         unsafe {
             AzDrawTargetStrokeRect(self.azure_draw_target,
                                    &mut rect.as_azure_rect(),
                                    pattern.azure_color_pattern,
                                    &mut stroke_options.as_azure_stroke_options(),
-                                   &mut draw_options.as_azure_draw_options());
+                                   &mut self.azure_draw_options(draw_options));
         }
     }
 
@@ -538,14 +1024,14 @@ impl DrawTarget {
                         dest: Rect<AzFloat>,
                         source: Rect<AzFloat>,
                         surf_options: DrawSurfaceOptions,
-                        options: DrawOptions) {
+                        options: Option<&DrawOptions>) {
         unsafe {
             AzDrawTargetDrawSurface(self.azure_draw_target,
                                     surface.azure_source_surface,
                                     &mut dest.as_azure_rect(),
                                     &mut source.as_azure_rect(),
                                     &mut surf_options.as_azure_draw_surface_options(),
-                                    &mut options.as_azure_draw_options());
+                                    &mut self.azure_draw_options(options));
         }
     }
 
@@ -567,6 +1053,46 @@ impl DrawTarget {
         }
     }
 
+    /// Draws a CSS box-shadow for the rounded rectangle `rect`.
+    ///
+    /// An outset shadow is the box grown by `spread`, moved by `offset` and blurred with a
+    /// Gaussian of standard deviation `blur_sigma`, and is not drawn inside the box itself. An
+    /// inset shadow is drawn inside the box, around a hole that is the box shrunk by `spread`
+    /// and moved by `offset`. Drawing is restricted to `clip` if there is one.
+    ///
+    /// Only the corners of the shadow are blurred, and the blurred corners are cached, so
+    /// large shadows and shadows repeated across a page are cheap.
+    pub fn draw_box_shadow(&self,
+                           rect: &Rect<AzFloat>,
+                           corner_radii: &CornerRadii,
+                           color: &Color,
+                           offset: &Point2D<AzFloat>,
+                           spread: AzFloat,
+                           blur_sigma: AzFloat,
+                           inset: bool,
+                           clip: Option<&Rect<AzFloat>>,
+                           draw_options: Option<&DrawOptions>) {
+        let azure_clip = clip.map(|clip| clip.as_azure_rect());
+        let azure_clip_ptr = match azure_clip {
+            Some(ref clip) => clip as *const AzRect,
+            None => ptr::null(),
+        };
+        unsafe {
+            AzDrawTargetDrawBoxShadow(self.azure_draw_target,
+                                      &rect.as_azure_rect(),
+                                      mem::transmute::<_,*const AzRectCornerRadii>(corner_radii),
+                                      mem::transmute::<_,*const AzColor>(color),
+                                      mem::transmute::<_,*const AzPoint>(offset),
+                                      spread,
+                                      blur_sigma,
+                                      inset,
+                                      azure_clip_ptr,
+                                      &mut self.azure_draw_options(draw_options))
+        }
+    }
+
+    /// Returns a snapshot of the current contents of this draw target. For dual draw targets this
+    /// is a dual surface holding a snapshot of each target, which can't be read back.
     pub fn snapshot(&self) -> SourceSurface {
         unsafe {
             let azure_surface = AzDrawTargetGetSnapshot(self.azure_draw_target);
@@ -574,6 +1100,68 @@ impl DrawTarget {
         }
     }
 
+    /// Copies the pixels of `rect` into a new surface in the target's format. Backends that
+    /// keep their pixels in memory copy only `rect`, without snapshotting the whole target.
+    /// `rect` must lie inside the target, and can't be empty, since there are no empty
+    /// surfaces.
+    pub fn snapshot_rect(&self, rect: Rect<i32>) -> DataSourceSurface {
+        self.assert_rect_inside(rect);
+        assert!(rect.size.width > 0 && rect.size.height > 0, "can't snapshot an empty rect");
+        let data_source_surface = unsafe {
+            AzDrawTargetSnapshotRect(self.azure_draw_target, &rect.as_azure_int_rect())
+        };
+        if data_source_surface.is_null() {
+            panic!("couldn't read back the draw target");
+        }
+        DataSourceSurface {
+            azure_data_source_surface: data_source_surface
+        }
+    }
+
+    /// Reads the pixels of `rect` into `data`, whose rows are `stride` bytes apart, converting
+    /// them to `format` if it isn't the target's format. `rect` must lie inside the target.
+    pub fn read_pixels(&self, rect: Rect<i32>, format: SurfaceFormat, data: &mut [u8], stride: i32) {
+        self.assert_rect_inside(rect);
+        assert!(stride >= 0);
+        let row_length = rect.size.width as uint * format.bytes_per_pixel();
+        assert!(stride as uint >= row_length);
+        if rect.size.width == 0 || rect.size.height == 0 {
+            return
+        }
+        assert!(data.len() >= (rect.size.height - 1) as uint * stride as uint + row_length);
+
+        if format as uint == self.get_format() as uint {
+            let read = unsafe {
+                AzDrawTargetReadPixels(self.azure_draw_target,
+                                       &rect.as_azure_int_rect(),
+                                       data.as_mut_ptr(),
+                                       stride)
+            };
+            if !read {
+                panic!("couldn't read back the draw target");
+            }
+            return
+        }
+
+        let surface = self.snapshot_rect(rect).convert(format);
+        let surface_stride = surface.stride() as uint;
+        surface.with_data(|source| {
+            for y in range(0, rect.size.height as uint) {
+                let source_row = source.slice(y * surface_stride, y * surface_stride + row_length);
+                data.slice_mut(y * stride as uint, y * stride as uint + row_length)
+                    .copy_from(source_row);
+            }
+        });
+    }
+
+    fn assert_rect_inside(&self, rect: Rect<i32>) {
+        let size = self.get_size();
+        assert!(rect.origin.x >= 0 && rect.origin.y >= 0 &&
+                rect.size.width >= 0 && rect.size.height >= 0 &&
+                rect.origin.x + rect.size.width <= size.width &&
+                rect.origin.y + rect.size.height <= size.height);
+    }
+
     pub fn create_source_surface_from_data(&self,
                                            data: &[u8],
                                            size: Size2D<i32>,
@@ -607,23 +1195,45 @@ impl DrawTarget {
 
     pub fn set_transform(&self, matrix: &Matrix2D<AzFloat>) {
         unsafe {
-            AzDrawTargetSetTransform(self.azure_draw_target, mem::transmute(matrix));
+            AzDrawTargetSetTransform(self.azure_draw_target, &mut matrix.as_azure_matrix());
+        }
+    }
+
+    pub fn get_transform(&self) -> Matrix2D<AzFloat> {
+        unsafe {
+            Matrix::from_azure_matrix(&AzDrawTargetGetTransform(self.azure_draw_target)).to_matrix2d()
+        }
+    }
+
+    /// Applies `matrix` before the current transform, so that it acts in the current user space.
+    pub fn concat_transform(&self, matrix: &Matrix2D<AzFloat>) {
+        unsafe {
+            AzDrawTargetConcatTransform(self.azure_draw_target, &matrix.as_azure_matrix());
         }
     }
 
     pub fn fill_glyphs(&self,
-                       azfontref: AzScaledFontRef,
-                       mut glyphbuf: struct__AzGlyphBuffer,
-                       azure_pattern: AzColorPatternRef,
-                       mut options: struct__AzDrawOptions,
-                       rendering_options: AzGlyphRenderingOptionsRef) {
+                       font: &ScaledFont,
+                       glyphs: &[Glyph],
+                       pattern: PatternRef,
+                       options: Option<&DrawOptions>,
+                       rendering_options: Option<&GlyphRenderingOptions>) {
+        let azure_glyphs: Vec<AzGlyph> = glyphs.iter().map(|glyph| glyph.as_azure_glyph()).collect();
+        let mut glyph_buffer = struct__AzGlyphBuffer {
+            mGlyphs: azure_glyphs.as_ptr(),
+            mNumGlyphs: azure_glyphs.len() as u32,
+        };
+        let azure_rendering_options = match rendering_options {
+            Some(rendering_options) => rendering_options.azure_glyph_rendering_options,
+            None => ptr::null_mut(),
+        };
         unsafe {
             AzDrawTargetFillGlyphs(self.azure_draw_target,
-                    azfontref,
-                    &mut glyphbuf,
-                    azure_pattern,
-                    &mut options,
-                    rendering_options);
+                                   font.get_ref(),
+                                   &mut glyph_buffer,
+                                   pattern.as_azure_pattern(),
+                                   &mut self.azure_draw_options(options),
+                                   azure_rendering_options);
         }
     }
 
@@ -636,52 +1246,541 @@ impl DrawTarget {
     }
 
     pub fn push_clip(&self, path: &Path) {
+        self.push_clip_with_id(path);
+    }
+
+    pub fn push_clip_rect(&self, rect: &Rect<AzFloat>) {
+        self.push_clip_rect_with_id(rect);
+    }
+
+    /// Pushes `path` as a clip, returning the id it was given.
+    fn push_clip_with_id(&self, path: &Path) -> uint {
+        let bounds = path.get_bounds(&self.get_transform());
         unsafe {
             AzDrawTargetPushClip(self.azure_draw_target,path.azure_path);
         }
+        self.record_clip(bounds)
     }
 
-    pub fn push_clip_rect(&self, rect: &Rect<AzFloat>) {
+    /// Pushes `rect` as a clip, returning the id it was given.
+    fn push_clip_rect_with_id(&self, rect: &Rect<AzFloat>) -> uint {
+        let bounds = Matrix::from_matrix2d(&self.get_transform()).transform_bounds(rect);
         unsafe {
             AzDrawTargetPushClipRect(self.azure_draw_target, &rect.as_azure_rect());
         }
+        self.record_clip(bounds)
+    }
+
+    fn record_clip(&self, bounds: Rect<AzFloat>) -> uint {
+        let mut state = self.state.lock();
+        let id = state.next_clip_id;
+        state.next_clip_id += 1;
+        state.clips.push(Clip {
+            id: id,
+            bounds: bounds,
+        });
+        id
     }
 
+    /// Pops the last clip pushed. Panics if there isn't one.
     pub fn pop_clip(&self) {
+        if self.state.lock().clips.pop().is_none() {
+            panic!("pop_clip() without a matching push_clip()");
+        }
         unsafe {
             AzDrawTargetPopClip(self.azure_draw_target);
         }
     }
-}
-
-// Ugly workaround for the lack of explicit self.
-pub fn clone_mutable_draw_target(draw_target: &mut DrawTarget) -> DrawTarget {
-    return draw_target.clone();
-}
-
-pub struct SourceSurface {
-    pub azure_source_surface: AzSourceSurfaceRef,
-}
 
-impl Drop for SourceSurface {
-    fn drop(&mut self) {
-        unsafe {
-            AzReleaseSourceSurface(self.azure_source_surface);
+    /// Pushes a clip that is popped when the returned guard goes out of scope.
+    pub fn push_clip_scoped<'a>(&'a self, path: &Path) -> ClipGuard<'a> {
+        ClipGuard {
+            draw_target: self,
+            id: self.push_clip_with_id(path),
         }
     }
-}
 
-impl SourceSurface {
-    pub fn new(azure_source_surface: AzSourceSurfaceRef) -> SourceSurface {
-        SourceSurface {
-            azure_source_surface: azure_source_surface
+    /// Pushes a clip rectangle that is popped when the returned guard goes out of scope.
+    pub fn push_clip_rect_scoped<'a>(&'a self, rect: &Rect<AzFloat>) -> ClipGuard<'a> {
+        ClipGuard {
+            draw_target: self,
+            id: self.push_clip_rect_with_id(rect),
         }
     }
-}
 
-pub struct GradientStops {
-    pub azure_gradient_stops: AzGradientStopsRef,
-}
+    /// Calls `f` with `path` pushed as a clip, popping it afterwards.
+    pub fn with_clip(&self, path: &Path, f: |&DrawTarget|) {
+        let _guard = self.push_clip_scoped(path);
+        f(self)
+    }
+
+    /// The number of clips pushed and not yet popped.
+    pub fn clip_depth(&self) -> uint {
+        self.state.lock().clips.len()
+    }
+
+    /// The bounds, in user space, of the area left to draw into by the target and the clips
+    /// pushed. Clips are intersected by their bounds, so this may be larger than the area a
+    /// clip path really lets through. Empty if the current transform can't be inverted.
+    pub fn clip_bounds(&self) -> Rect<AzFloat> {
+        let size = self.get_size();
+        let mut bounds = Rect(Point2D(0.0, 0.0),
+                              Size2D(size.width as AzFloat, size.height as AzFloat));
+        for clip in self.state.lock().clips.iter() {
+            bounds = intersect_rects(&bounds, &clip.bounds);
+        }
+        match Matrix::from_matrix2d(&self.get_transform()).inverse() {
+            Some(inverse) => inverse.transform_bounds(&bounds),
+            None => Rect(Point2D(0.0, 0.0), Size2D(0.0, 0.0)),
+        }
+    }
+
+    pub fn global_alpha(&self) -> AzFloat {
+        self.state.lock().global_alpha
+    }
+
+    pub fn set_global_alpha(&self, alpha: AzFloat) {
+        self.state.lock().global_alpha = alpha;
+    }
+
+    pub fn composition_op(&self) -> CompositionOp {
+        self.state.lock().composition_op
+    }
+
+    pub fn set_composition_op(&self, op: CompositionOp) {
+        self.state.lock().composition_op = op;
+    }
+
+    /// Azure takes the alpha and composition op with each drawing command, so the global ones
+    /// only take effect through the options returned here. Drawing commands use these when they
+    /// aren't given any.
+    pub fn draw_options(&self) -> DrawOptions {
+        let state = self.state.lock();
+        let mut draw_options = DrawOptions::new(state.global_alpha, 0);
+        draw_options.set_composition_op(state.composition_op);
+        draw_options.set_antialias_mode(AZ_AA_DEFAULT as u8);
+        draw_options
+    }
+
+    /// Records the transform, clip depth, global alpha and composition op, for `restore`.
+    pub fn save(&self) {
+        let transform = self.get_transform();
+        let mut state = self.state.lock();
+        let saved = SavedState {
+            transform: transform,
+            clip_depth: state.clips.len(),
+            global_alpha: state.global_alpha,
+            composition_op: state.composition_op,
+        };
+        state.saved.push(saved);
+    }
+
+    /// Returns to the state recorded by the last `save`, popping the clips pushed since.
+    /// Panics if nothing was saved, or if clips pushed before the `save` have been popped.
+    pub fn restore(&self) {
+        match self.pop_saved_state() {
+            Ok(()) => {}
+            Err(message) => panic!("{}", message),
+        }
+    }
+
+    /// Does the work of `restore`, returning what went wrong instead of panicking. A state is
+    /// restored as far as it can be even if clips pushed before it was saved were popped.
+    fn pop_saved_state(&self) -> Result<(), &'static str> {
+        let (saved, clips_to_pop) = {
+            let mut state = self.state.lock();
+            let saved = match state.saved.pop() {
+                Some(saved) => saved,
+                None => return Err("restore() without a matching save()"),
+            };
+            let clips_to_pop = if state.clips.len() > saved.clip_depth {
+                state.clips.len() - saved.clip_depth
+            } else {
+                0
+            };
+            state.clips.truncate(saved.clip_depth);
+            state.global_alpha = saved.global_alpha;
+            state.composition_op = saved.composition_op;
+            (saved, clips_to_pop)
+        };
+        for _ in range(0, clips_to_pop) {
+            unsafe {
+                AzDrawTargetPopClip(self.azure_draw_target);
+            }
+        }
+        self.set_transform(&saved.transform);
+        if self.clip_depth() < saved.clip_depth {
+            return Err("clips pushed before save() were popped before restore()")
+        }
+        Ok(())
+    }
+
+    /// The number of states saved and not yet restored.
+    pub fn save_depth(&self) -> uint {
+        self.state.lock().saved.len()
+    }
+
+    /// Saves the state, returning a guard that restores it when it goes out of scope.
+    pub fn scoped_save<'a>(&'a self) -> StateGuard<'a> {
+        let depth = self.save_depth();
+        self.save();
+        StateGuard {
+            draw_target: self,
+            depth: depth,
+        }
+    }
+}
+
+/// The drawing operations supported by a draw target. Painting code should be written against
+/// this trait rather than `DrawTarget` directly, so that recorders, test doubles and targets that
+/// forward to several others can be substituted without going through the FFI.
+pub trait DrawTargetApi {
+    fn get_size(&self) -> AzIntSize;
+
+    fn flush(&self);
+
+    fn clear_rect(&self, rect: &Rect<AzFloat>);
+
+    fn fill(&self, path: &Path, pattern: &ColorPattern, draw_options: Option<&DrawOptions>);
+
+    fn fill_rect(&self,
+                 rect: &Rect<AzFloat>,
+                 pattern: PatternRef,
+                 draw_options: Option<&DrawOptions>);
+
+    fn stroke_line(&self,
+                   start: Point2D<AzFloat>,
+                   end: Point2D<AzFloat>,
+                   pattern: &ColorPattern,
+                   stroke_options: &StrokeOptions,
+                   draw_options: Option<&DrawOptions>);
+
+    fn stroke_rect(&self,
+                   rect: &Rect<AzFloat>,
+                   pattern: &ColorPattern,
+                   stroke_options: &StrokeOptions,
+                   draw_options: Option<&DrawOptions>);
+
+    fn draw_surface(&self,
+                    surface: SourceSurface,
+                    dest: Rect<AzFloat>,
+                    source: Rect<AzFloat>,
+                    surf_options: DrawSurfaceOptions,
+                    options: Option<&DrawOptions>);
+
+    fn draw_surface_with_shadow(&self,
+                                surface: SourceSurface,
+                                dest: &Point2D<AzFloat>,
+                                color: &Color,
+                                offset: &Point2D<AzFloat>,
+                                sigma: AzFloat,
+                                operator: CompositionOp);
+
+    fn snapshot(&self) -> SourceSurface;
+
+    fn create_source_surface_from_data(&self,
+                                       data: &[u8],
+                                       size: Size2D<i32>,
+                                       stride: i32,
+                                       format: SurfaceFormat)
+                                       -> SourceSurface;
+
+    fn create_gradient_stops(&self,
+                             gradient_stops: &[GradientStop],
+                             extend_mode: ExtendMode)
+                             -> GradientStops;
+
+    fn set_transform(&self, matrix: &Matrix2D<AzFloat>);
+
+    fn get_transform(&self) -> Matrix2D<AzFloat>;
+
+    fn concat_transform(&self, matrix: &Matrix2D<AzFloat>);
+
+    fn fill_glyphs(&self,
+                   font: &ScaledFont,
+                   glyphs: &[Glyph],
+                   pattern: PatternRef,
+                   options: Option<&DrawOptions>,
+                   rendering_options: Option<&GlyphRenderingOptions>);
+
+    fn create_path_builder(&self) -> PathBuilder;
+
+    fn push_clip(&self, path: &Path);
+
+    fn push_clip_rect(&self, rect: &Rect<AzFloat>);
+
+    fn pop_clip(&self);
+
+    fn push_clip_scoped<'a>(&'a self, path: &Path) -> ClipGuard<'a>;
+
+    fn push_clip_rect_scoped<'a>(&'a self, rect: &Rect<AzFloat>) -> ClipGuard<'a>;
+
+    fn with_clip(&self, path: &Path, f: |&DrawTarget|);
+
+    fn clip_depth(&self) -> uint;
+
+    fn clip_bounds(&self) -> Rect<AzFloat>;
+
+    fn draw_box_shadow(&self,
+                       rect: &Rect<AzFloat>,
+                       corner_radii: &CornerRadii,
+                       color: &Color,
+                       offset: &Point2D<AzFloat>,
+                       spread: AzFloat,
+                       blur_sigma: AzFloat,
+                       inset: bool,
+                       clip: Option<&Rect<AzFloat>>,
+                       draw_options: Option<&DrawOptions>);
+
+    fn snapshot_rect(&self, rect: Rect<i32>) -> DataSourceSurface;
+
+    fn read_pixels(&self, rect: Rect<i32>, format: SurfaceFormat, data: &mut [u8], stride: i32);
+
+    fn global_alpha(&self) -> AzFloat;
+
+    fn set_global_alpha(&self, alpha: AzFloat);
+
+    fn composition_op(&self) -> CompositionOp;
+
+    fn set_composition_op(&self, op: CompositionOp);
+
+    fn draw_options(&self) -> DrawOptions;
+
+    fn save(&self);
+
+    fn restore(&self);
+
+    fn save_depth(&self) -> uint;
+
+    fn scoped_save<'a>(&'a self) -> StateGuard<'a>;
+}
+
+// Method resolution prefers the inherent methods, so each of these forwards to the
+// libazure-backed implementation above.
+impl DrawTargetApi for DrawTarget {
+    fn get_size(&self) -> AzIntSize {
+        self.get_size()
+    }
+
+    fn flush(&self) {
+        self.flush()
+    }
+
+    fn clear_rect(&self, rect: &Rect<AzFloat>) {
+        self.clear_rect(rect)
+    }
+
+    fn fill(&self, path: &Path, pattern: &ColorPattern, draw_options: Option<&DrawOptions>) {
+        self.fill(path, pattern, draw_options)
+    }
+
+    fn fill_rect(&self,
+                 rect: &Rect<AzFloat>,
+                 pattern: PatternRef,
+                 draw_options: Option<&DrawOptions>) {
+        self.fill_rect(rect, pattern, draw_options)
+    }
+
+    fn stroke_line(&self,
+                   start: Point2D<AzFloat>,
+                   end: Point2D<AzFloat>,
+                   pattern: &ColorPattern,
+                   stroke_options: &StrokeOptions,
+                   draw_options: Option<&DrawOptions>) {
+        self.stroke_line(start, end, pattern, stroke_options, draw_options)
+    }
+
+    fn stroke_rect(&self,
+                   rect: &Rect<AzFloat>,
+                   pattern: &ColorPattern,
+                   stroke_options: &StrokeOptions,
+                   draw_options: Option<&DrawOptions>) {
+        self.stroke_rect(rect, pattern, stroke_options, draw_options)
+    }
+
+    fn draw_surface(&self,
+                    surface: SourceSurface,
+                    dest: Rect<AzFloat>,
+                    source: Rect<AzFloat>,
+                    surf_options: DrawSurfaceOptions,
+                    options: Option<&DrawOptions>) {
+        self.draw_surface(surface, dest, source, surf_options, options)
+    }
+
+    fn draw_surface_with_shadow(&self,
+                                surface: SourceSurface,
+                                dest: &Point2D<AzFloat>,
+                                color: &Color,
+                                offset: &Point2D<AzFloat>,
+                                sigma: AzFloat,
+                                operator: CompositionOp) {
+        self.draw_surface_with_shadow(surface, dest, color, offset, sigma, operator)
+    }
+
+    fn snapshot(&self) -> SourceSurface {
+        self.snapshot()
+    }
+
+    fn create_source_surface_from_data(&self,
+                                       data: &[u8],
+                                       size: Size2D<i32>,
+                                       stride: i32,
+                                       format: SurfaceFormat)
+                                       -> SourceSurface {
+        self.create_source_surface_from_data(data, size, stride, format)
+    }
+
+    fn create_gradient_stops(&self,
+                             gradient_stops: &[GradientStop],
+                             extend_mode: ExtendMode)
+                             -> GradientStops {
+        self.create_gradient_stops(gradient_stops, extend_mode)
+    }
+
+    fn set_transform(&self, matrix: &Matrix2D<AzFloat>) {
+        self.set_transform(matrix)
+    }
+
+    fn get_transform(&self) -> Matrix2D<AzFloat> {
+        self.get_transform()
+    }
+
+    fn concat_transform(&self, matrix: &Matrix2D<AzFloat>) {
+        self.concat_transform(matrix)
+    }
+
+    fn fill_glyphs(&self,
+                   font: &ScaledFont,
+                   glyphs: &[Glyph],
+                   pattern: PatternRef,
+                   options: Option<&DrawOptions>,
+                   rendering_options: Option<&GlyphRenderingOptions>) {
+        self.fill_glyphs(font, glyphs, pattern, options, rendering_options)
+    }
+
+    fn create_path_builder(&self) -> PathBuilder {
+        self.create_path_builder()
+    }
+
+    fn push_clip(&self, path: &Path) {
+        self.push_clip(path)
+    }
+
+    fn push_clip_rect(&self, rect: &Rect<AzFloat>) {
+        self.push_clip_rect(rect)
+    }
+
+    fn pop_clip(&self) {
+        self.pop_clip()
+    }
+
+    fn push_clip_scoped<'a>(&'a self, path: &Path) -> ClipGuard<'a> {
+        self.push_clip_scoped(path)
+    }
+
+    fn push_clip_rect_scoped<'a>(&'a self, rect: &Rect<AzFloat>) -> ClipGuard<'a> {
+        self.push_clip_rect_scoped(rect)
+    }
+
+    fn with_clip(&self, path: &Path, f: |&DrawTarget|) {
+        self.with_clip(path, f)
+    }
+
+    fn clip_depth(&self) -> uint {
+        self.clip_depth()
+    }
+
+    fn clip_bounds(&self) -> Rect<AzFloat> {
+        self.clip_bounds()
+    }
+
+    fn draw_box_shadow(&self,
+                       rect: &Rect<AzFloat>,
+                       corner_radii: &CornerRadii,
+                       color: &Color,
+                       offset: &Point2D<AzFloat>,
+                       spread: AzFloat,
+                       blur_sigma: AzFloat,
+                       inset: bool,
+                       clip: Option<&Rect<AzFloat>>,
+                       draw_options: Option<&DrawOptions>) {
+        self.draw_box_shadow(rect, corner_radii, color, offset, spread, blur_sigma, inset, clip,
+                             draw_options)
+    }
+
+    fn snapshot_rect(&self, rect: Rect<i32>) -> DataSourceSurface {
+        self.snapshot_rect(rect)
+    }
+
+    fn read_pixels(&self, rect: Rect<i32>, format: SurfaceFormat, data: &mut [u8], stride: i32) {
+        self.read_pixels(rect, format, data, stride)
+    }
+
+    fn global_alpha(&self) -> AzFloat {
+        self.global_alpha()
+    }
+
+    fn set_global_alpha(&self, alpha: AzFloat) {
+        self.set_global_alpha(alpha)
+    }
+
+    fn composition_op(&self) -> CompositionOp {
+        self.composition_op()
+    }
+
+    fn set_composition_op(&self, op: CompositionOp) {
+        self.set_composition_op(op)
+    }
+
+    fn draw_options(&self) -> DrawOptions {
+        self.draw_options()
+    }
+
+    fn save(&self) {
+        self.save()
+    }
+
+    fn restore(&self) {
+        self.restore()
+    }
+
+    fn save_depth(&self) -> uint {
+        self.save_depth()
+    }
+
+    fn scoped_save<'a>(&'a self) -> StateGuard<'a> {
+        self.scoped_save()
+    }
+}
+
+// Ugly workaround for the lack of explicit self.
+pub fn clone_mutable_draw_target(draw_target: &mut DrawTarget) -> DrawTarget {
+    return draw_target.clone();
+}
+
+pub struct SourceSurface {
+    pub azure_source_surface: AzSourceSurfaceRef,
+}
+
+impl Drop for SourceSurface {
+    fn drop(&mut self) {
+        unsafe {
+            AzReleaseSourceSurface(self.azure_source_surface);
+        }
+    }
+}
+
+impl SourceSurface {
+    pub fn new(azure_source_surface: AzSourceSurfaceRef) -> SourceSurface {
+        SourceSurface {
+            azure_source_surface: azure_source_surface
+        }
+    }
+}
+
+pub struct GradientStops {
+    pub azure_gradient_stops: AzGradientStopsRef,
+}
 
 impl Drop for GradientStops {
     fn drop(&mut self) {
@@ -754,10 +1853,23 @@ impl SourceSurface {
         let data_source_surface = unsafe {
             AzSourceSurfaceGetDataSurface(self.azure_source_surface)
         };
+        if data_source_surface.is_null() {
+            panic!("this source surface doesn't support readback");
+        }
         DataSourceSurface {
             azure_data_source_surface: data_source_surface
         }
     }
+
+    /// Writes the surface to `writer` as a PNG. See `DataSourceSurface::write_png`.
+    pub fn write_png(&self, writer: &mut Writer) -> IoResult<()> {
+        self.get_data_surface().write_png(writer)
+    }
+
+    /// Writes the surface to the file at `path` as a PNG. See `DataSourceSurface::write_png`.
+    pub fn write_png_file(&self, path: &FilePath) -> IoResult<()> {
+        self.get_data_surface().write_png_file(path)
+    }
 }
 
 impl SourceSurfaceMethods for SourceSurface {
@@ -777,6 +1889,19 @@ impl Drop for DataSourceSurface {
 }
 
 impl DataSourceSurface {
+    /// Creates a zero-filled surface in memory.
+    pub fn new(size: Size2D<i32>, format: SurfaceFormat) -> DataSourceSurface {
+        let data_source_surface = unsafe {
+            AzCreateDataSourceSurface(&size.as_azure_int_size(), format.as_azure_surface_format())
+        };
+        if data_source_surface.is_null() {
+            panic!("couldn't create a data source surface");
+        }
+        DataSourceSurface {
+            azure_data_source_surface: data_source_surface
+        }
+    }
+
     pub fn with_data(&self, f: |&[u8]|) {
         unsafe {
             let buf = AzDataSourceSurfaceGetData(self.azure_data_source_surface) as *const u8;
@@ -785,6 +1910,14 @@ impl DataSourceSurface {
         }
     }
 
+    pub fn with_data_mut(&mut self, f: |&mut [u8]|) {
+        unsafe {
+            let buf = AzDataSourceSurfaceGetData(self.azure_data_source_surface);
+            let len = self.stride() * self.size().height;
+            slice::raw::mut_buf_as_slice(buf, len as uint, f);
+        }
+    }
+
     pub fn stride(&self) -> i32 {
         unsafe {
             AzDataSourceSurfaceGetStride(self.azure_data_source_surface)
@@ -795,6 +1928,297 @@ impl DataSourceSurface {
     pub fn get_size(&self) -> Size2D<i32> {
         self.size()
     }
+
+    /// Creates a surface in `format` from this one. Opaque formats gain an opaque alpha, and
+    /// losing the alpha leaves the premultiplied colors as they would look over black. An A8
+    /// surface converts to black with its alpha, and converting to A8 keeps only the alpha.
+    pub fn convert(&self, format: SurfaceFormat) -> DataSourceSurface {
+        let size = self.size();
+        let mut result = DataSourceSurface::new(size, format);
+        let source_format = self.format();
+        let source_stride = self.stride() as uint;
+        let result_stride = result.stride() as uint;
+        self.with_data(|source| {
+            result.with_data_mut(|dest| {
+                for y in range(0, size.height as uint) {
+                    let source_row = source.slice_from(y * source_stride);
+                    let dest_row = dest.slice_from_mut(y * result_stride);
+                    for x in range(0, size.width as uint) {
+                        store_pixel(format, dest_row, x, load_pixel(source_format, source_row, x));
+                    }
+                }
+            });
+        });
+        result
+    }
+
+    /// Creates a B8G8R8A8 surface from tightly packed 4-byte pixels in the given channel order,
+    /// premultiplying them first if their alpha is straight.
+    pub fn from_pixels(size: Size2D<i32>, data: &[u8], order: ChannelOrder, alpha: AlphaType)
+                       -> DataSourceSurface {
+        let width = size.width as uint;
+        assert!(data.len() == width * size.height as uint * 4);
+        let mut surface = DataSourceSurface::new(size, B8G8R8A8);
+        let stride = surface.stride() as uint;
+        surface.with_data_mut(|dest| {
+            for y in range(0, size.height as uint) {
+                let source_row = data.slice(y * width * 4, (y + 1) * width * 4);
+                let dest_row = dest.slice_from_mut(y * stride);
+                for (x, pixel) in source_row.chunks(4).enumerate() {
+                    let bgra = match order {
+                        RGBAOrder => [pixel[2], pixel[1], pixel[0], pixel[3]],
+                        BGRAOrder => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                    };
+                    let bgra = match alpha {
+                        PremultipliedAlpha => bgra,
+                        StraightAlpha => [premultiply(bgra[0], bgra[3]),
+                                          premultiply(bgra[1], bgra[3]),
+                                          premultiply(bgra[2], bgra[3]),
+                                          bgra[3]],
+                    };
+                    store_pixel(B8G8R8A8, dest_row, x, bgra);
+                }
+            }
+        });
+        surface
+    }
+
+    /// Returns the surface's pixels, in any format, as tightly packed 4-byte pixels in the
+    /// given channel order, un-premultiplying them if straight alpha is asked for.
+    pub fn to_pixels(&self, order: ChannelOrder, alpha: AlphaType) -> Vec<u8> {
+        let size = self.size();
+        let format = self.format();
+        let stride = self.stride() as uint;
+        let mut pixels = Vec::with_capacity(size.width as uint * size.height as uint * 4);
+        self.with_data(|data| {
+            for y in range(0, size.height as uint) {
+                let row = data.slice_from(y * stride);
+                for x in range(0, size.width as uint) {
+                    let bgra = load_pixel(format, row, x);
+                    let bgra = match alpha {
+                        PremultipliedAlpha => bgra,
+                        StraightAlpha => [unpremultiply(bgra[0], bgra[3]),
+                                          unpremultiply(bgra[1], bgra[3]),
+                                          unpremultiply(bgra[2], bgra[3]),
+                                          bgra[3]],
+                    };
+                    match order {
+                        RGBAOrder => pixels.push_all(&[bgra[2], bgra[1], bgra[0], bgra[3]]),
+                        BGRAOrder => pixels.push_all(&bgra),
+                    }
+                }
+            }
+        });
+        pixels
+    }
+
+    /// Writes the surface to `path` as a PNG. Pixels with alpha are un-premultiplied into an
+    /// RGBA image. Opaque formats become an RGB image, and A8 pixels become the alpha of a
+    /// black grey-and-alpha image. Every format is written with 8 bits per channel, so
+    /// R16G16B16A16 loses precision.
+    pub fn write_png(&self, writer: &mut Writer) -> IoResult<()> {
+        // The PNG library only encodes to files, so the image goes through a temporary one.
+        let dir = try!(TempDir::new("azure-png"));
+        let path = dir.path().join("surface.png");
+        try!(self.write_png_file(&path));
+        let bytes = try!(File::open(&path).read_to_end());
+        writer.write(bytes.as_slice())
+    }
+
+    /// Writes the surface to the file at `path` as a PNG. See `write_png`.
+    pub fn write_png_file(&self, path: &FilePath) -> IoResult<()> {
+        let rgba = self.to_pixels(RGBAOrder, StraightAlpha);
+        let pixels = match self.format() {
+            B8G8R8A8 | R8G8B8A8 | R16G16B16A16 => png::RGBA8(rgba),
+            B8G8R8X8 | R8G8B8X8 | R5G6B5 => {
+                png::RGB8(rgba.as_slice().chunks(4).flat_map(|pixel| {
+                    pixel.slice_to(3).iter().map(|&channel| channel)
+                }).collect())
+            }
+            A8 => png::KA8(rgba.as_slice().chunks(4).flat_map(|pixel| {
+                vec!(0, pixel[3]).into_iter()
+            }).collect()),
+        };
+        let size = self.size();
+        let mut image = png::Image {
+            width: size.width as u32,
+            height: size.height as u32,
+            pixels: pixels,
+        };
+        match png::store_png(&mut image, path) {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                Err(IoError {
+                    kind: OtherIoError,
+                    desc: "couldn't encode the PNG image",
+                    detail: Some(error),
+                })
+            }
+        }
+    }
+
+    /// Decodes a PNG from `reader` into a premultiplied B8G8R8A8 surface, or a B8G8R8X8 one if
+    /// the image has no alpha channel. The surface is created by `draw_target`, so it can be
+    /// drawn there without being converted.
+    pub fn from_png(reader: &mut Reader, draw_target: &DrawTarget)
+                    -> IoResult<DataSourceSurface> {
+        let bytes = try!(reader.read_to_end());
+        let image = match png::load_png_from_memory(bytes.as_slice()) {
+            Ok(image) => image,
+            Err(error) => {
+                return Err(IoError {
+                    kind: InvalidInput,
+                    desc: "couldn't decode the PNG image",
+                    detail: Some(error),
+                })
+            }
+        };
+
+        let mut data = Vec::with_capacity(image.width as uint * image.height as uint * 4);
+        let format = match image.pixels {
+            png::K8(ref pixels) => {
+                for &k in pixels.iter() {
+                    data.push_all(&[k, k, k, 255]);
+                }
+                B8G8R8X8
+            }
+            png::KA8(ref pixels) => {
+                for pixel in pixels.as_slice().chunks(2) {
+                    let k = premultiply(pixel[0], pixel[1]);
+                    data.push_all(&[k, k, k, pixel[1]]);
+                }
+                B8G8R8A8
+            }
+            png::RGB8(ref pixels) => {
+                for pixel in pixels.as_slice().chunks(3) {
+                    data.push_all(&[pixel[2], pixel[1], pixel[0], 255]);
+                }
+                B8G8R8X8
+            }
+            png::RGBA8(ref pixels) => {
+                for pixel in pixels.as_slice().chunks(4) {
+                    let a = pixel[3];
+                    data.push_all(&[premultiply(pixel[2], a),
+                                    premultiply(pixel[1], a),
+                                    premultiply(pixel[0], a),
+                                    a]);
+                }
+                B8G8R8A8
+            }
+        };
+
+        let size = Size2D(image.width as i32, image.height as i32);
+        let surface = draw_target.create_source_surface_from_data(data.as_slice(),
+                                                                  size,
+                                                                  size.width * 4,
+                                                                  format);
+        Ok(surface.get_data_surface())
+    }
+}
+
+/// The order of the channels in 4-byte pixels passed to `DataSourceSurface::from_pixels` or
+/// returned by `DataSourceSurface::to_pixels`.
+#[deriving(Clone, PartialEq)]
+pub enum ChannelOrder {
+    RGBAOrder,
+    BGRAOrder,
+}
+
+/// Whether the color channels of pixels have been multiplied by their alpha, as Azure's own
+/// surfaces are, or are straight, as most image decoders produce them.
+#[deriving(Clone, PartialEq)]
+pub enum AlphaType {
+    PremultipliedAlpha,
+    StraightAlpha,
+}
+
+/// Reads pixel `x` of a row in `format` as premultiplied B, G, R and A.
+fn load_pixel(format: SurfaceFormat, row: &[u8], x: uint) -> [u8, ..4] {
+    match format {
+        B8G8R8A8 => [row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]],
+        B8G8R8X8 => [row[x * 4], row[x * 4 + 1], row[x * 4 + 2], 255],
+        R5G6B5 => {
+            let pixel = row[x * 2] as u16 | (row[x * 2 + 1] as u16 << 8);
+            let (r, g, b) = ((pixel >> 11) as u8, (pixel >> 5 & 0x3f) as u8, (pixel & 0x1f) as u8);
+            [b << 3 | b >> 2, g << 2 | g >> 4, r << 3 | r >> 2, 255]
+        }
+        A8 => [0, 0, 0, row[x]],
+        R8G8B8A8 => [row[x * 4 + 2], row[x * 4 + 1], row[x * 4], row[x * 4 + 3]],
+        R8G8B8X8 => [row[x * 4 + 2], row[x * 4 + 1], row[x * 4], 255],
+        R16G16B16A16 => {
+            let narrow = |channel: uint| {
+                ((load_u16(row, x * 8 + channel * 2) as uint * 255 + 32767) / 65535) as u8
+            };
+            [narrow(2), narrow(1), narrow(0), narrow(3)]
+        }
+    }
+}
+
+/// Writes premultiplied B, G, R and A to pixel `x` of a row in `format`.
+fn store_pixel(format: SurfaceFormat, row: &mut [u8], x: uint, bgra: [u8, ..4]) {
+    match format {
+        B8G8R8A8 => {
+            row.slice_mut(x * 4, x * 4 + 4).copy_from(&bgra);
+        }
+        B8G8R8X8 => {
+            row.slice_mut(x * 4, x * 4 + 4).copy_from(&[bgra[0], bgra[1], bgra[2], 255]);
+        }
+        R5G6B5 => {
+            let quantize = |value: u8, max: uint| ((value as uint * max + 127) / 255) as u16;
+            let pixel = quantize(bgra[2], 31) << 11 | quantize(bgra[1], 63) << 5 |
+                quantize(bgra[0], 31);
+            row[x * 2] = pixel as u8;
+            row[x * 2 + 1] = (pixel >> 8) as u8;
+        }
+        A8 => row[x] = bgra[3],
+        R8G8B8A8 => {
+            row.slice_mut(x * 4, x * 4 + 4).copy_from(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+        }
+        R8G8B8X8 => {
+            row.slice_mut(x * 4, x * 4 + 4).copy_from(&[bgra[2], bgra[1], bgra[0], 255]);
+        }
+        R16G16B16A16 => {
+            for (channel, &value) in [bgra[2], bgra[1], bgra[0], bgra[3]].iter().enumerate() {
+                store_u16(row, x * 8 + channel * 2, value as u16 * 257);
+            }
+        }
+    }
+}
+
+/// The overlap of two rectangles, which is empty, at the origin of `a`, if they don't overlap.
+fn intersect_rects(a: &Rect<AzFloat>, b: &Rect<AzFloat>) -> Rect<AzFloat> {
+    let left = a.origin.x.max(b.origin.x);
+    let top = a.origin.y.max(b.origin.y);
+    let right = (a.origin.x + a.size.width).min(b.origin.x + b.size.width);
+    let bottom = (a.origin.y + a.size.height).min(b.origin.y + b.size.height);
+    if right <= left || bottom <= top {
+        return Rect(a.origin, Size2D(0.0, 0.0))
+    }
+    Rect(Point2D(left, top), Size2D(right - left, bottom - top))
+}
+
+/// Reads a native-endian `u16` from `offset` in `row`.
+fn load_u16(row: &[u8], offset: uint) -> u16 {
+    unsafe { mem::transmute::<[u8, ..2], u16>([row[offset], row[offset + 1]]) }
+}
+
+/// Writes a native-endian `u16` to `offset` in `row`.
+fn store_u16(row: &mut [u8], offset: uint, value: u16) {
+    let bytes = unsafe { mem::transmute::<u16, [u8, ..2]>(value) };
+    row[offset] = bytes[0];
+    row[offset + 1] = bytes[1];
+}
+
+fn premultiply(value: u8, alpha: u8) -> u8 {
+    ((value as uint * alpha as uint + 127) / 255) as u8
+}
+
+fn unpremultiply(value: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        0
+    } else {
+        cmp::min((value as uint * 255 + alpha as uint / 2) / alpha as uint, 255) as u8
+    }
 }
 
 impl SourceSurfaceMethods for DataSourceSurface {
@@ -815,22 +2239,32 @@ impl Drop for Path {
     }
 }
 
+impl Path {
+    /// The bounds of the path once transformed by `transform`.
+    pub fn get_bounds(&self, transform: &Matrix2D<AzFloat>) -> Rect<AzFloat> {
+        let bounds = unsafe {
+            AzPathGetBounds(self.azure_path, &transform.as_azure_matrix())
+        };
+        Rect(Point2D(bounds.x, bounds.y), Size2D(bounds.width, bounds.height))
+    }
+}
+
 pub struct PathBuilder {
     pub azure_path_builder: AzPathBuilderRef
 }
 
 impl PathBuilder {
     pub fn move_to(&self, point: Point2D<AzFloat>) {
-        let mut az_point = point.as_azure_point();
+        let az_point = point.as_azure_point();
         unsafe {
-            AzPathBuilderMoveTo(self.azure_path_builder, &mut az_point);
+            AzPathBuilderMoveTo(self.azure_path_builder, &az_point);
         }
     }
 
     pub fn line_to(&self, point: Point2D<AzFloat>) {
-        let mut az_point = point.as_azure_point();
+        let az_point = point.as_azure_point();
         unsafe {
-            AzPathBuilderLineTo(self.azure_path_builder, &mut az_point);
+            AzPathBuilderLineTo(self.azure_path_builder, &az_point);
         }
     }
 
@@ -892,7 +2326,7 @@ impl LinearGradientPattern {
                     AzCreateLinearGradientPattern(mem::transmute::<_,*const AzPoint>(begin),
                                                   mem::transmute::<_,*const AzPoint>(end),
                                                   stops.azure_gradient_stops,
-                                                  mem::transmute::<_,*const AzMatrix>(matrix)),
+                                                  &matrix.as_azure_matrix()),
             }
         }
     }
@@ -919,3 +2353,181 @@ pub fn current_gl_context() -> AzGLContext {
         AzSkiaGetCurrentGLContext()
     }
 }
+
+/// Which versions of Azure's own blur and half-scaling code run on this machine. Skia picks its
+/// SIMD code by itself and isn't covered.
+#[deriving(Clone, PartialEq, Show)]
+pub struct CpuFeatures {
+    /// Whether the processor supports SSE2.
+    pub sse2_supported: bool,
+    /// Whether Azure was built with its SSE2 code paths.
+    pub sse2_compiled: bool,
+    /// Whether the SSE2 code paths are taken: they were compiled, the processor supports them
+    /// and they haven't been turned off with `force_scalar_paths`.
+    pub sse2_enabled: bool,
+}
+
+pub fn cpu_features() -> CpuFeatures {
+    unsafe {
+        CpuFeatures {
+            sse2_supported: AzCPUSupportsSSE2(),
+            sse2_compiled: AzSSE2PathsCompiled(),
+            sse2_enabled: AzHasSSE2(),
+        }
+    }
+}
+
+/// Makes Azure's blur and half-scaling code take their scalar paths even where SSE2 is
+/// available, or lets them use SSE2 again. This affects every thread, including ones that are
+/// already drawing.
+pub fn force_scalar_paths(force: bool) {
+    unsafe {
+        AzSetSSE2Disabled(force);
+    }
+}
+
+/// The result of a blur: a new surface and the area it covers, in the coordinates of the
+/// surface that was blurred.
+pub struct BlurredSurface {
+    pub surface: DataSourceSurface,
+    pub rect: Rect<i32>,
+}
+
+/// Returns the box blur radius that approximates a Gaussian blur with the given standard
+/// deviation in each direction.
+pub fn calculate_blur_radius(std_deviation: Point2D<AzFloat>) -> Size2D<i32> {
+    let radius = unsafe {
+        AzCalculateBlurRadius(&std_deviation.as_azure_point())
+    };
+    Size2D(radius.width, radius.height)
+}
+
+/// Spreads and then blurs an A8 or B8G8R8A8 surface with a triple box blur. The result is
+/// larger than the surface by the spread and blur radii on every side.
+///
+/// Only the parts of the result needed to draw `dirty_rect` are computed, and the inside of
+/// `skip_rect`, which is usually covered by other content, is left unblurred. Both are in the
+/// coordinates of `surface`. Returns `None` if nothing needs to be drawn.
+pub fn box_blur(surface: &DataSourceSurface,
+                spread_radius: Size2D<i32>,
+                blur_radius: Size2D<i32>,
+                dirty_rect: Option<Rect<AzFloat>>,
+                skip_rect: Option<Rect<AzFloat>>)
+                -> Option<BlurredSurface> {
+    match surface.format() {
+        A8 | B8G8R8A8 => {}
+        _ => panic!("only A8 and B8G8R8A8 surfaces can be blurred"),
+    }
+
+    let azure_dirty_rect = dirty_rect.map(|rect| rect.as_azure_rect());
+    let azure_skip_rect = skip_rect.map(|rect| rect.as_azure_rect());
+    let optional_rect_ptr = |rect: &Option<AzRect>| {
+        match *rect {
+            Some(ref rect) => rect as *const AzRect,
+            None => ptr::null(),
+        }
+    };
+    let mut azure_rect = struct__AzIntRect { x: 0, y: 0, width: 0, height: 0 };
+    let blurred = unsafe {
+        AzBlurDataSourceSurface(surface.azure_data_source_surface,
+                                &spread_radius.as_azure_int_size(),
+                                &blur_radius.as_azure_int_size(),
+                                optional_rect_ptr(&azure_dirty_rect),
+                                optional_rect_ptr(&azure_skip_rect),
+                                &mut azure_rect)
+    };
+    if blurred.is_null() {
+        return None;
+    }
+    Some(BlurredSurface {
+        surface: DataSourceSurface {
+            azure_data_source_surface: blurred
+        },
+        rect: Rect(Point2D(azure_rect.x, azure_rect.y),
+                   Size2D(azure_rect.width, azure_rect.height)),
+    })
+}
+
+/// Approximates a Gaussian blur with the given standard deviation, after spreading the
+/// surface. See `box_blur`.
+pub fn gaussian_blur(surface: &DataSourceSurface,
+                     std_deviation: Point2D<AzFloat>,
+                     spread_radius: Size2D<i32>,
+                     dirty_rect: Option<Rect<AzFloat>>,
+                     skip_rect: Option<Rect<AzFloat>>)
+                     -> Option<BlurredSurface> {
+    box_blur(surface, spread_radius, calculate_blur_radius(std_deviation), dirty_rect, skip_rect)
+}
+
+/// The filters `scale_surface` can resample with.
+#[repr(i32)]
+#[deriving(Clone, PartialEq)]
+pub enum ResizeMethod {
+    /// The fastest filter that still looks acceptable.
+    GoodResize = 0,
+    /// Close to `BestResize`, trading some quality for speed.
+    BetterResize = 1,
+    /// The highest quality filter.
+    BestResize = 2,
+    /// Averages every source pixel under each destination pixel. Enlarges by repeating pixels.
+    BoxResize = 3,
+    /// A 1-cycle Hamming window.
+    Hamming1Resize = 4,
+    /// A 2-cycle Lanczos window.
+    Lanczos2Resize = 5,
+    /// A 3-cycle Lanczos window, which keeps edges sharp.
+    Lanczos3Resize = 6,
+}
+
+impl ResizeMethod {
+    fn as_azure_resize_method(self) -> AzResizeMethod {
+        self as AzResizeMethod
+    }
+}
+
+/// Resamples a B8G8R8A8, B8G8R8X8 or A8 surface to `new_size` with a proper filter, which
+/// avoids the aliasing of shrinking it with `draw_surface`.
+pub fn scale_surface(surface: &DataSourceSurface,
+                     new_size: Size2D<i32>,
+                     method: ResizeMethod)
+                     -> DataSourceSurface {
+    match surface.format() {
+        B8G8R8A8 | B8G8R8X8 | A8 => {}
+        _ => panic!("only B8G8R8A8, B8G8R8X8 and A8 surfaces can be scaled"),
+    }
+
+    let scaled = unsafe {
+        AzScaleDataSourceSurface(surface.azure_data_source_surface,
+                                 &new_size.as_azure_int_size(),
+                                 method.as_azure_resize_method())
+    };
+    if scaled.is_null() {
+        panic!("couldn't scale a data source surface");
+    }
+    DataSourceSurface {
+        azure_data_source_surface: scaled
+    }
+}
+
+/// Halves a B8G8R8A8 or B8G8R8X8 surface in each direction, like successive mipmap levels,
+/// for as long as the result stays larger than `min_size`. Each dimension that was halved ends
+/// up larger than `min_size` but at most about twice it, which makes the result a cheap
+/// starting point for a `scale_surface` down to a thumbnail.
+pub fn half_scale_surface(surface: &DataSourceSurface, min_size: Size2D<i32>)
+                          -> DataSourceSurface {
+    match surface.format() {
+        B8G8R8A8 | B8G8R8X8 => {}
+        _ => panic!("only B8G8R8A8 and B8G8R8X8 surfaces can be half-scaled"),
+    }
+
+    let scaled = unsafe {
+        AzHalfScaleDataSourceSurface(surface.azure_data_source_surface,
+                                     &min_size.as_azure_int_size())
+    };
+    if scaled.is_null() {
+        panic!("couldn't half-scale a data source surface");
+    }
+    DataSourceSurface {
+        azure_data_source_surface: scaled
+    }
+}
@@ -36,3 +36,7 @@ extern { }
 #[link(name = "QuartzCore", kind = "framework")]
 #[link(name = "ApplicationServices", kind = "framework")]
 extern { }
+
+#[cfg(feature = "cairo")]
+#[link(name = "cairo")]
+extern { }
@@ -2,15 +2,28 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use azure::{AzScaledFontRef, AzFloat};
-use azure::{struct__AzNativeFont};
+use azure::{AzScaledFontRef, AzFloat, AzGlyph};
+use azure::{struct__AzNativeFont, struct__AzGlyphBuffer};
+use azure::{AzScaledFontGetPathForGlyphs, AzScaledFontCopyGlyphsToBuilder};
+use azure::{AzScaledFontGetMetrics, AzScaledFontGetGlyphMetrics, AzScaledFontGetGlyphIndex};
+use azure::{struct__AzFontMetrics, struct__AzRect};
+use azure::{AzScaledFontSetSyntheticStyle, AzFontSlant, AzFontOptions};
+use azure::{AZ_FONT_SLANT_NORMAL, AZ_FONT_SLANT_ITALIC, AZ_FONT_SLANT_OBLIQUE};
 
 use azure_hl::{BackendType,SkiaBackend};
+use azure_hl::{DrawTarget, Glyph, Path, PathBuilder};
+#[cfg(any(target_os="linux", target_os = "android"))]
+use azure_hl::CairoBackend;
 use azure::{AzCreateScaledFontForNativeFont, AzReleaseScaledFont};
 
+use geom::point::Point2D;
+use geom::rect::Rect;
+use geom::size::Size2D;
 use libc::c_void;
+use std::c_str::ToCStr;
 use std::mem;
 use std::ptr;
+use sync::Arc;
 
 #[cfg(target_os="macos")]
 use scaled_font::macos::*;
@@ -39,14 +52,77 @@ pub mod android {
 
 pub type SkTypeface = *mut c_void;
 
+/// Describes the face `native_font` was loaded from, so that Skia loads the same one.
+#[cfg(any(target_os="linux", target_os = "android"))]
+unsafe fn font_options_for_face(native_font: FT_Face) -> *mut AzFontOptions {
+    use azure::AzCreateFontOptionsForFreetypeFace;
+
+    AzCreateFontOptionsForFreetypeFace(native_font as *mut c_void)
+}
+
 #[cfg(any(target_os="linux", target_os = "android"))]
 pub enum FontInfo<'a> {
     NativeFont(FT_Face),
     FontData(&'a Vec<u8>),
+    Descriptor(&'a FontDescriptor),
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub enum FontStyle {
+    NormalFontStyle,
+    ItalicFontStyle,
+    ObliqueFontStyle,
+}
+
+impl FontStyle {
+    fn as_azure_font_slant(self) -> AzFontSlant {
+        match self {
+            NormalFontStyle => AZ_FONT_SLANT_NORMAL,
+            ItalicFontStyle => AZ_FONT_SLANT_ITALIC,
+            ObliqueFontStyle => AZ_FONT_SLANT_OBLIQUE,
+        }
+    }
+}
+
+/// Selects a face from the system's fonts in CSS terms. On Linux the face is matched with
+/// fontconfig; elsewhere only the family name and whether the face is bold or italic are used.
+#[deriving(Clone, PartialEq, Show)]
+pub struct FontDescriptor {
+    pub family: String,
+    /// The weight, from 100 (thin) to 900 (black); 400 is normal and 700 is bold.
+    pub weight: u16,
+    pub style: FontStyle,
+    /// The width as a percentage of the normal width, from 50 to 200.
+    pub stretch: u16,
+}
+
+impl FontDescriptor {
+    pub fn new(family: &str) -> FontDescriptor {
+        FontDescriptor {
+            family: family.to_string(),
+            weight: 400,
+            style: NormalFontStyle,
+            stretch: 100,
+        }
+    }
+}
+
+/// Vertical metrics of a font at its size. The ascent is measured upwards from the baseline;
+/// the descent and the underline position are measured downwards.
+#[deriving(Clone, PartialEq, Show)]
+pub struct FontMetrics {
+    pub ascent: AzFloat,
+    pub descent: AzFloat,
+    pub leading: AzFloat,
+    pub x_height: AzFloat,
+    pub underline_position: AzFloat,
+    pub underline_thickness: AzFloat,
 }
 
 pub struct ScaledFont {
     azure_scaled_font: AzScaledFontRef,
+    /// The font file the font was created from, which libazure reads without copying.
+    data: Option<Arc<Vec<u8>>>,
 }
 
 impl Drop for ScaledFont {
@@ -62,11 +138,112 @@ impl ScaledFont {
         self.azure_scaled_font
     }
 
+    pub fn metrics(&self) -> FontMetrics {
+        let mut metrics = struct__AzFontMetrics {
+            mAscent: 0.0,
+            mDescent: 0.0,
+            mLeading: 0.0,
+            mXHeight: 0.0,
+            mUnderlinePosition: 0.0,
+            mUnderlineThickness: 0.0,
+        };
+        unsafe {
+            if !AzScaledFontGetMetrics(self.azure_scaled_font, &mut metrics) {
+                panic!("this font doesn't support metrics queries");
+            }
+        }
+        FontMetrics {
+            ascent: metrics.mAscent,
+            descent: metrics.mDescent,
+            leading: metrics.mLeading,
+            x_height: metrics.mXHeight,
+            underline_position: metrics.mUnderlinePosition,
+            underline_thickness: metrics.mUnderlineThickness,
+        }
+    }
+
+    /// Emboldens and/or slants the glyphs of this font when drawing, outlining and measuring
+    /// them, for faces that have no real bold or italic variant.
+    pub fn set_synthetic_style(&mut self, synthetic_bold: bool, synthetic_oblique: bool) {
+        unsafe {
+            AzScaledFontSetSyntheticStyle(self.azure_scaled_font, synthetic_bold, synthetic_oblique);
+        }
+    }
+
+    /// Returns the horizontal advance of the glyph with the given index.
+    pub fn glyph_advance(&self, index: u32) -> AzFloat {
+        let (advance, _) = self.glyph_metrics(index);
+        advance
+    }
+
+    /// Returns the ink bounds of the glyph with the given index, relative to its origin on the
+    /// baseline.
+    pub fn glyph_bounds(&self, index: u32) -> Rect<AzFloat> {
+        let (_, bounds) = self.glyph_metrics(index);
+        bounds
+    }
+
+    fn glyph_metrics(&self, index: u32) -> (AzFloat, Rect<AzFloat>) {
+        let mut advance = 0.0;
+        let mut bounds = struct__AzRect { x: 0.0, y: 0.0, width: 0.0, height: 0.0 };
+        unsafe {
+            if !AzScaledFontGetGlyphMetrics(self.azure_scaled_font, index, &mut advance, &mut bounds) {
+                panic!("this font doesn't support metrics queries");
+            }
+        }
+        (advance, Rect(Point2D(bounds.x, bounds.y), Size2D(bounds.width, bounds.height)))
+    }
+
+    /// Returns the index of the glyph for `character`, or `None` if the font doesn't cover it.
+    pub fn glyph_index(&self, character: char) -> Option<u32> {
+        match unsafe { AzScaledFontGetGlyphIndex(self.azure_scaled_font, character as u32) } {
+            0 => None,
+            index => Some(index),
+        }
+    }
+
+    /// Returns the outlines of the given glyphs as a path usable with `draw_target`, or `None` if
+    /// this font can't produce outlines for that draw target.
+    pub fn path_for_glyphs(&self, glyphs: &[Glyph], draw_target: &DrawTarget) -> Option<Path> {
+        let azure_glyphs: Vec<AzGlyph> = glyphs.iter().map(|glyph| glyph.as_azure_glyph()).collect();
+        let glyph_buffer = struct__AzGlyphBuffer {
+            mGlyphs: azure_glyphs.as_ptr(),
+            mNumGlyphs: azure_glyphs.len() as u32,
+        };
+        unsafe {
+            let azure_path = AzScaledFontGetPathForGlyphs(self.azure_scaled_font,
+                                                          &glyph_buffer,
+                                                          draw_target.azure_draw_target);
+            if azure_path.is_null() {
+                return None;
+            }
+            Some(Path {
+                azure_path: azure_path
+            })
+        }
+    }
+
+    /// Appends the outlines of the given glyphs to the figures in `builder`. Returns false, and
+    /// leaves `builder` unchanged, if this font can't produce outlines.
+    pub fn copy_glyphs_to_builder(&self, glyphs: &[Glyph], builder: &PathBuilder) -> bool {
+        let azure_glyphs: Vec<AzGlyph> = glyphs.iter().map(|glyph| glyph.as_azure_glyph()).collect();
+        let glyph_buffer = struct__AzGlyphBuffer {
+            mGlyphs: azure_glyphs.as_ptr(),
+            mNumGlyphs: azure_glyphs.len() as u32,
+        };
+        unsafe {
+            AzScaledFontCopyGlyphsToBuilder(self.azure_scaled_font,
+                                            &glyph_buffer,
+                                            builder.azure_path_builder)
+        }
+    }
+
     #[cfg(any(target_os="linux", target_os = "android"))]
     pub fn new(backend: BackendType, font_info: FontInfo, size: AzFloat)
         -> ScaledFont {
         use azure::AZ_NATIVE_FONT_SKIA_FONT_FACE;
-        use azure::{AzCreateFontOptionsForData, AzCreateFontOptionsForName, AzDestroyFontOptions};
+        use azure::{AzCreateFontOptionsForData, AzCreateFontOptionsForDescriptor};
+        use azure::AzDestroyFontOptions;
 
         let mut azure_native_font = struct__AzNativeFont {
             mType: 0,
@@ -77,27 +254,99 @@ impl ScaledFont {
             SkiaBackend => {
                 unsafe {
                     let options = match font_info {
-                        NativeFont(native_font) => {
-                            // NOTE: azure style flags and freetype style flags are the same in the lowest 2 bits
-                            let style = ((*native_font).style_flags & 3) as u32;
-                            AzCreateFontOptionsForName(&*(*native_font).family_name, style)
-                        },
+                        NativeFont(native_font) => font_options_for_face(native_font),
                         FontData(bytes) => {
                             AzCreateFontOptionsForData(bytes.as_ptr(), bytes.len() as u32)
                         },
+                        Descriptor(descriptor) => {
+                            descriptor.family.with_c_str(|family| {
+                                AzCreateFontOptionsForDescriptor(family,
+                                                                 descriptor.weight,
+                                                                 descriptor.style.as_azure_font_slant(),
+                                                                 descriptor.stretch)
+                            })
+                        },
                     };
                     azure_native_font.mType = AZ_NATIVE_FONT_SKIA_FONT_FACE;
                     azure_native_font.mFont = mem::transmute(options);
                     let azure_native_font_ptr = &mut azure_native_font;
                     let azure_scaled_font = AzCreateScaledFontForNativeFont(azure_native_font_ptr, size);
                     AzDestroyFontOptions(options);
-                    ScaledFont { azure_scaled_font: azure_scaled_font }
+                    ScaledFont { azure_scaled_font: azure_scaled_font, data: None }
                 }
             }
+            #[cfg(feature = "cairo")]
+            CairoBackend => {
+                use azure::{AzCreateCairoScaledFontForFreetypeFace, AzCreateScaledFontWithCairo};
+                use azure::AzReleaseCairoScaledFont;
+
+                let native_font = match font_info {
+                    NativeFont(native_font) => native_font,
+                    FontData(_) | Descriptor(_) => {
+                        panic!("the cairo backend can only create scaled fonts for native fonts")
+                    }
+                };
+                unsafe {
+                    // The native font description is the same as for Skia; the cairo scaled font
+                    // is attached to it. The FT_Face must outlive the returned font, as cairo
+                    // doesn't take ownership of it.
+                    let options = font_options_for_face(native_font);
+                    let cairo_font = AzCreateCairoScaledFontForFreetypeFace(
+                        native_font as *mut c_void, size);
+                    azure_native_font.mType = AZ_NATIVE_FONT_SKIA_FONT_FACE;
+                    azure_native_font.mFont = mem::transmute(options);
+                    let azure_scaled_font = AzCreateScaledFontWithCairo(&mut azure_native_font,
+                                                                        size,
+                                                                        cairo_font);
+                    AzReleaseCairoScaledFont(cairo_font);
+                    AzDestroyFontOptions(options);
+                    ScaledFont { azure_scaled_font: azure_scaled_font, data: None }
+                }
+            }
+            #[cfg(not(feature = "cairo"))]
+            CairoBackend => {
+                panic!("cairo scaled fonts need azure to be built with the `cairo` feature")
+            }
             _ => { panic!("don't know how to make a scaled font for this backend"); }
         }
     }
 
+    /// Creates a Skia font from the face at `face_index` in a TrueType/OpenType font file or
+    /// collection. The data is shared with the font rather than copied, and stays alive for
+    /// as long as Skia reads it. Returns `None` if the data isn't a font Skia can read or has no
+    /// face at `face_index`.
+    #[cfg(any(target_os="linux", target_os = "android"))]
+    pub fn from_truetype_data(data: Arc<Vec<u8>>, face_index: u32, size: AzFloat)
+                              -> Option<ScaledFont> {
+        use azure::{AzCreateScaledFontForTrueTypeData, AZ_FONT_SKIA};
+
+        // Skia's caches can outlive the font, so libazure holds a reference of its own and
+        // drops it through `release_font_data`.
+        extern "C" fn release_font_data(closure: *mut c_void) {
+            unsafe {
+                let _: Box<Arc<Vec<u8>>> = mem::transmute(closure);
+            }
+        }
+
+        unsafe {
+            let closure: *mut c_void = mem::transmute(box data.clone());
+            let azure_scaled_font = AzCreateScaledFontForTrueTypeData(data.as_ptr() as *mut u8,
+                                                                      data.len() as u32,
+                                                                      face_index,
+                                                                      size,
+                                                                      AZ_FONT_SKIA,
+                                                                      release_font_data,
+                                                                      closure);
+            if azure_scaled_font.is_null() {
+                return None;
+            }
+            Some(ScaledFont {
+                azure_scaled_font: azure_scaled_font,
+                data: Some(data),
+            })
+        }
+    }
+
     /// Mac-specific function to create a font for the given backend.
     #[cfg(target_os="macos")]
     pub fn new(backend: BackendType, native_font: &CGFont, size: AzFloat) -> ScaledFont {
@@ -125,7 +374,8 @@ impl ScaledFont {
         unsafe {
             let azure_scaled_font = AzCreateScaledFontForNativeFont(&mut azure_native_font, size);
             ScaledFont {
-                azure_scaled_font: azure_scaled_font
+                azure_scaled_font: azure_scaled_font,
+                data: None,
             }
         }
     }
@@ -15,3 +15,945 @@ fn sanity_check() {
 
     unsafe { AzSanityCheck() };
 }
+
+/// Finds the `azure-unittest` binary that `makefile.cargo` builds into this package's output
+/// directory, which Cargo places under `native/` next to the test executable. It's built for
+/// the test profile, or whenever `AZURE_BUILD_UNITTESTS` is set.
+#[cfg(test)]
+fn find_unittest_binary() -> Option<Path> {
+    use std::io::fs;
+    use std::os;
+
+    let native_dir = os::self_exe_path().unwrap().join("native");
+    let dirs = match fs::readdir(&native_dir) {
+        Ok(dirs) => dirs,
+        Err(_) => return None,
+    };
+    for dir in dirs.iter() {
+        let is_azure_dir = dir.filename_str().map_or(false, |name| name.starts_with("azure-"));
+        let binary = dir.join("azure-unittest");
+        if is_azure_dir && binary.exists() {
+            return Some(binary);
+        }
+    }
+    None
+}
+
+/// Checks that the checked-in FFI bindings are what gen.py makes of the current header.
+#[test]
+fn bindings_match_header() {
+    use std::io::{Command, File, TempDir};
+    use std::os;
+
+    let python = os::getenv("PYTHON").unwrap_or("python".to_string());
+    let dir = TempDir::new("azure-bindings").unwrap();
+    let generated = dir.path().join("azure.rs");
+    let status = match Command::new(python.as_slice())
+                               .arg("gen.py")
+                               .arg("src/azure-c.h")
+                               .arg(&generated)
+                               .status() {
+        Ok(status) => status,
+        Err(error) => {
+            panic!("couldn't run {} to check the bindings; set PYTHON: {}", python, error)
+        }
+    };
+    assert!(status.success(), "gen.py failed: {}", status);
+
+    let expected = File::open(&Path::new("src/azure.rs")).read_to_end().unwrap();
+    let actual = File::open(&generated).read_to_end().unwrap();
+    if expected != actual {
+        panic!("src/azure.rs is out of date; run `make -f makefile.cargo bindings`");
+    }
+}
+
+/// Runs libazure's C++ unit tests and reports each failing test along with its messages.
+#[test]
+fn libazure_unit_tests() {
+    use std::io::Command;
+
+    let binary = match find_unittest_binary() {
+        Some(binary) => binary,
+        None => panic!("azure-unittest wasn't built; set AZURE_BUILD_UNITTESTS to build it"),
+    };
+    let output = Command::new(binary).output().unwrap();
+    let output_text = String::from_utf8(output.output).unwrap();
+
+    // The test runner prints "--- RUNNING TESTS: <group> ---" before each group, then
+    // "Test (<name>): " followed by any failure messages and finally "PASSED" or "FAILED".
+    let mut failures = Vec::new();
+    let mut group = "";
+    let mut test = "";
+    let mut messages = Vec::new();
+    for line in output_text.as_slice().lines() {
+        if line.starts_with("--- RUNNING TESTS: ") {
+            group = line.slice_from(19).trim_right_chars('-').trim();
+            continue;
+        }
+        let mut result = line;
+        if line.starts_with("Test (") {
+            let end = line.find_str("): ").unwrap();
+            test = line.slice(6, end);
+            result = line.slice_from(end + 3);
+            messages.clear();
+        }
+        match result {
+            "PASSED" => {}
+            "FAILED" => {
+                failures.push(format!("{} / {}: {}", group, test, messages.connect("; ")));
+            }
+            "" => {}
+            message => messages.push(message),
+        }
+    }
+
+    if !failures.is_empty() || !output.status.success() {
+        panic!("libazure unit tests failed ({}):\n{}", output.status, failures.connect("\n"));
+    }
+}
+
+/// Returns the struct sizes, field offsets and enum values that the C side reports.
+#[cfg(test)]
+fn layout_entries() -> Vec<(String, i64)> {
+    use azure::AzGetLayoutEntries;
+    use std::c_str::CString;
+    use std::slice;
+
+    let mut count = 0;
+    let mut entries = Vec::new();
+    unsafe {
+        let raw_entries = AzGetLayoutEntries(&mut count);
+        slice::raw::buf_as_slice(raw_entries, count as uint, |raw_entries| {
+            for entry in raw_entries.iter() {
+                let name = CString::new(entry.mName, false);
+                entries.push((name.as_str().unwrap().to_string(), entry.mValue));
+            }
+        });
+    }
+    entries
+}
+
+#[cfg(test)]
+fn check_layout(entries: &Vec<(String, i64)>, name: &str, rust_value: uint) {
+    match entries.iter().find(|&&(ref entry_name, _)| entry_name.as_slice() == name) {
+        Some(&(_, c_value)) => {
+            assert!(c_value == rust_value as i64,
+                    "{}: C reports {} but Rust has {}", name, c_value, rust_value);
+        }
+        None => panic!("{}: not reported by AzGetLayoutEntries", name),
+    }
+}
+
+macro_rules! offset_of(
+    ($ty:ty, $field:ident) => (unsafe {
+        let value: $ty = ::std::mem::zeroed();
+        (&value.$field as *const _ as uint) - (&value as *const $ty as uint)
+    })
+)
+
+/// Checks that a Rust struct has the same size and field offsets as the named C struct.
+macro_rules! check_struct(
+    ($entries:expr, $ty:ty, $c_name:expr, $($field:ident => $c_field:expr),+) => ({
+        check_layout($entries, $c_name, ::std::mem::size_of::<$ty>());
+        $(check_layout($entries,
+                       format!("{}.{}", $c_name, $c_field).as_slice(),
+                       offset_of!($ty, $field));)+
+    })
+)
+
+#[test]
+fn ffi_struct_layouts_match_c() {
+    use azure::{AzColor, AzGradientStop, AzIntRect, AzRect, AzIntPoint, AzPoint, AzIntSize};
+    use azure::{AzSize, AzMatrix, AzDrawOptions, AzStrokeOptions, AzDrawSurfaceOptions};
+    use azure::{AzGlyph, AzGlyphBuffer, AzNativeFont, AzFontMetrics, AzRectCornerRadii};
+
+    let entries = layout_entries();
+    check_struct!(&entries, AzColor, "AzColor", r => "r", g => "g", b => "b", a => "a");
+    check_struct!(&entries, AzGradientStop, "AzGradientStop",
+                  offset => "offset", color => "color");
+    check_struct!(&entries, AzIntRect, "AzIntRect",
+                  x => "x", y => "y", width => "width", height => "height");
+    check_struct!(&entries, AzRect, "AzRect",
+                  x => "x", y => "y", width => "width", height => "height");
+    check_struct!(&entries, AzIntPoint, "AzIntPoint", x => "x", y => "y");
+    check_struct!(&entries, AzPoint, "AzPoint", x => "x", y => "y");
+    check_struct!(&entries, AzIntSize, "AzIntSize", width => "width", height => "height");
+    check_struct!(&entries, AzSize, "AzSize", width => "width", height => "height");
+    check_struct!(&entries, AzMatrix, "AzMatrix",
+                  _11 => "_11", _12 => "_12", _21 => "_21", _22 => "_22", _31 => "_31", _32 => "_32");
+    check_struct!(&entries, AzDrawOptions, "AzDrawOptions",
+                  mAlpha => "mAlpha", fields => "fields");
+    check_struct!(&entries, AzStrokeOptions, "AzStrokeOptions",
+                  mLineWidth => "mLineWidth",
+                  mMiterLimit => "mMiterLimit",
+                  mDashPattern => "mDashPattern",
+                  mDashLength => "mDashLength",
+                  mDashOffset => "mDashOffset",
+                  fields => "fields");
+    check_struct!(&entries, AzDrawSurfaceOptions, "AzDrawSurfaceOptions", fields => "fields");
+    check_struct!(&entries, AzGlyph, "AzGlyph", mIndex => "mIndex", mPosition => "mPosition");
+    check_struct!(&entries, AzGlyphBuffer, "AzGlyphBuffer",
+                  mGlyphs => "mGlyphs", mNumGlyphs => "mNumGlyphs");
+    check_struct!(&entries, AzNativeFont, "AzNativeFont", mType => "mType", mFont => "mFont");
+    check_struct!(&entries, AzRectCornerRadii, "AzRectCornerRadii",
+                  mTopLeft => "mTopLeft",
+                  mTopRight => "mTopRight",
+                  mBottomRight => "mBottomRight",
+                  mBottomLeft => "mBottomLeft");
+    check_struct!(&entries, AzFontMetrics, "AzFontMetrics",
+                  mAscent => "mAscent",
+                  mDescent => "mDescent",
+                  mLeading => "mLeading",
+                  mXHeight => "mXHeight",
+                  mUnderlinePosition => "mUnderlinePosition",
+                  mUnderlineThickness => "mUnderlineThickness");
+}
+
+/// The high-level types that `azure_hl` transmutes into their C counterparts.
+#[test]
+fn high_level_struct_layouts_match_c() {
+    use azure::AzFloat;
+    use azure_hl::{Color, GradientStop, CornerRadii};
+    use geom::matrix2d::Matrix2D;
+    use geom::point::Point2D;
+
+    let entries = layout_entries();
+    check_struct!(&entries, Color, "AzColor", r => "r", g => "g", b => "b", a => "a");
+    check_struct!(&entries, GradientStop, "AzGradientStop",
+                  offset => "offset", color => "color");
+    check_struct!(&entries, Matrix2D<AzFloat>, "AzMatrix",
+                  m11 => "_11", m12 => "_12", m21 => "_21", m22 => "_22", m31 => "_31", m32 => "_32");
+    check_struct!(&entries, Point2D<AzFloat>, "AzPoint", x => "x", y => "y");
+    check_struct!(&entries, CornerRadii, "AzRectCornerRadii",
+                  top_left => "mTopLeft",
+                  top_right => "mTopRight",
+                  bottom_right => "mBottomRight",
+                  bottom_left => "mBottomLeft");
+}
+
+#[test]
+fn high_level_enum_values_match_c() {
+    use azure_hl::{OverOp, AddOp, AtopOp, OutOp, InOp, SourceOp, DestInOp, DestOutOp};
+    use azure_hl::{DestOverOp, DestAtopOp, XorOp, MultiplyOp, ScreenOp, OverlayOp, DarkenOp};
+    use azure_hl::{LightenOp, ColorDodgeOp, ColorBurnOp, HardLightOp, SoftLightOp, DifferenceOp};
+    use azure_hl::{ExclusionOp, HueOp, SaturationOp, ColorOp, LuminosityOp};
+    use azure_hl::{B8G8R8A8, B8G8R8X8, R5G6B5, A8, R8G8B8A8, R8G8B8X8, R16G16B16A16};
+    use azure_hl::{Linear, Point};
+    use azure_hl::{NoBackend, Direct2DBackend, CoreGraphicsBackend, CoreGraphicsAcceleratedBackend};
+    use azure_hl::{CairoBackend, SkiaBackend, RecordingBackend};
+    use azure_hl::{ExtendClamp, ExtendRepeat, ExtendReflect};
+    use azure_hl::{NoHinting, LightHinting, NormalHinting, FullHinting};
+    use azure_hl::{GoodResize, BetterResize, BestResize, BoxResize, Hamming1Resize};
+    use azure_hl::{Lanczos2Resize, Lanczos3Resize};
+
+    let entries = layout_entries();
+
+    let composition_ops = [
+        ("AZ_OP_OVER", OverOp as uint),
+        ("AZ_OP_ADD", AddOp as uint),
+        ("AZ_OP_ATOP", AtopOp as uint),
+        ("AZ_OP_OUT", OutOp as uint),
+        ("AZ_OP_IN", InOp as uint),
+        ("AZ_OP_SOURCE", SourceOp as uint),
+        ("AZ_OP_DEST_IN", DestInOp as uint),
+        ("AZ_OP_DEST_OUT", DestOutOp as uint),
+        ("AZ_OP_DEST_OVER", DestOverOp as uint),
+        ("AZ_OP_DEST_ATOP", DestAtopOp as uint),
+        ("AZ_OP_XOR", XorOp as uint),
+        ("AZ_OP_MULTIPLY", MultiplyOp as uint),
+        ("AZ_OP_SCREEN", ScreenOp as uint),
+        ("AZ_OP_OVERLAY", OverlayOp as uint),
+        ("AZ_OP_DARKEN", DarkenOp as uint),
+        ("AZ_OP_LIGHTEN", LightenOp as uint),
+        ("AZ_OP_COLOR_DODGE", ColorDodgeOp as uint),
+        ("AZ_OP_COLOR_BURN", ColorBurnOp as uint),
+        ("AZ_OP_HARD_LIGHT", HardLightOp as uint),
+        ("AZ_OP_SOFT_LIGHT", SoftLightOp as uint),
+        ("AZ_OP_DIFFERENCE", DifferenceOp as uint),
+        ("AZ_OP_EXCLUSION", ExclusionOp as uint),
+        ("AZ_OP_HUE", HueOp as uint),
+        ("AZ_OP_SATURATION", SaturationOp as uint),
+        ("AZ_OP_COLOR", ColorOp as uint),
+        ("AZ_OP_LUMINOSITY", LuminosityOp as uint),
+    ];
+    let surface_formats = [
+        ("AZ_FORMAT_B8G8R8A8", B8G8R8A8 as uint),
+        ("AZ_FORMAT_B8G8R8X8", B8G8R8X8 as uint),
+        ("AZ_FORMAT_R5G6B5", R5G6B5 as uint),
+        ("AZ_FORMAT_A8", A8 as uint),
+        ("AZ_FORMAT_R8G8B8A8", R8G8B8A8 as uint),
+        ("AZ_FORMAT_R8G8B8X8", R8G8B8X8 as uint),
+        ("AZ_FORMAT_R16G16B16A16", R16G16B16A16 as uint),
+    ];
+    let filters = [
+        ("AZ_FILTER_LINEAR", Linear as uint),
+        ("AZ_FILTER_POINT", Point as uint),
+    ];
+    let backends = [
+        ("AZ_BACKEND_NONE", NoBackend.as_azure_backend_type() as uint),
+        ("AZ_BACKEND_DIRECT2D", Direct2DBackend.as_azure_backend_type() as uint),
+        ("AZ_BACKEND_COREGRAPHICS", CoreGraphicsBackend.as_azure_backend_type() as uint),
+        ("AZ_BACKEND_COREGRAPHICS_ACCELERATED",
+         CoreGraphicsAcceleratedBackend.as_azure_backend_type() as uint),
+        ("AZ_BACKEND_CAIRO", CairoBackend.as_azure_backend_type() as uint),
+        ("AZ_BACKEND_SKIA", SkiaBackend.as_azure_backend_type() as uint),
+        ("AZ_BACKEND_RECORDING", RecordingBackend.as_azure_backend_type() as uint),
+    ];
+    let extend_modes = [
+        ("AZ_EXTEND_CLAMP", ExtendClamp as uint),
+        ("AZ_EXTEND_REPEAT", ExtendRepeat as uint),
+        ("AZ_EXTEND_REFLECT", ExtendReflect as uint),
+    ];
+    let font_hintings = [
+        ("AZ_FONT_HINTING_NONE", NoHinting as uint),
+        ("AZ_FONT_HINTING_LIGHT", LightHinting as uint),
+        ("AZ_FONT_HINTING_NORMAL", NormalHinting as uint),
+        ("AZ_FONT_HINTING_FULL", FullHinting as uint),
+    ];
+    let resize_methods = [
+        ("AZ_RESIZE_GOOD", GoodResize as uint),
+        ("AZ_RESIZE_BETTER", BetterResize as uint),
+        ("AZ_RESIZE_BEST", BestResize as uint),
+        ("AZ_RESIZE_BOX", BoxResize as uint),
+        ("AZ_RESIZE_HAMMING1", Hamming1Resize as uint),
+        ("AZ_RESIZE_LANCZOS2", Lanczos2Resize as uint),
+        ("AZ_RESIZE_LANCZOS3", Lanczos3Resize as uint),
+    ];
+
+    for values in [composition_ops.as_slice(),
+                   surface_formats.as_slice(),
+                   filters.as_slice(),
+                   backends.as_slice(),
+                   extend_modes.as_slice(),
+                   font_hintings.as_slice(),
+                   resize_methods.as_slice()].iter() {
+        for &(name, value) in values.iter() {
+            check_layout(&entries, name, value);
+        }
+    }
+}
+
+/// Makes a surface whose rows, without any padding, are `bytes`.
+fn surface_from_rows(size: ::geom::size::Size2D<i32>,
+                     format: ::azure_hl::SurfaceFormat,
+                     bytes: &[u8])
+                     -> ::azure_hl::DataSourceSurface {
+    use azure_hl::DataSourceSurface;
+
+    let row_length = size.width as uint * format.bytes_per_pixel();
+    assert_eq!(bytes.len(), row_length * size.height as uint);
+    let mut surface = DataSourceSurface::new(size, format);
+    let stride = surface.stride() as uint;
+    surface.with_data_mut(|data| {
+        for (y, row) in bytes.chunks(row_length).enumerate() {
+            data.slice_mut(y * stride, y * stride + row_length).copy_from(row);
+        }
+    });
+    surface
+}
+
+/// Reads back the rows of `surface`, leaving out the padding at the end of each.
+fn surface_rows(surface: &::azure_hl::DataSourceSurface) -> Vec<u8> {
+    use azure_hl::SourceSurfaceMethods;
+
+    let size = surface.size();
+    let row_length = size.width as uint * surface.format().bytes_per_pixel();
+    let stride = surface.stride() as uint;
+    let mut bytes = Vec::with_capacity(row_length * size.height as uint);
+    surface.with_data(|data| {
+        for y in range(0, size.height as uint) {
+            bytes.push_all(data.slice(y * stride, y * stride + row_length));
+        }
+    });
+    bytes
+}
+
+#[test]
+fn blur_treats_each_bgra_channel_like_a8() {
+    use azure_hl::{A8, B8G8R8A8, box_blur};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    // An opaque 4x4 square in the middle of a 16x16 surface.
+    let square = |bytes_per_pixel: uint| {
+        let mut bytes = Vec::new();
+        for y in range(0, 16u) {
+            for x in range(0, 16u) {
+                let inside = x >= 6 && x < 10 && y >= 6 && y < 10;
+                bytes.grow(bytes_per_pixel, if inside { 255u8 } else { 0 });
+            }
+        }
+        bytes
+    };
+    let alpha = surface_from_rows(Size2D(16, 16), A8, square(1).as_slice());
+    let color = surface_from_rows(Size2D(16, 16), B8G8R8A8, square(4).as_slice());
+
+    let spread_radius = Size2D(1, 1);
+    let blur_radius = Size2D(2, 2);
+    let blurred_alpha = box_blur(&alpha, spread_radius, blur_radius, None, None).unwrap();
+    let blurred_color = box_blur(&color, spread_radius, blur_radius, None, None).unwrap();
+    assert_eq!(blurred_alpha.rect, Rect(Point2D(-3, -3), Size2D(22, 22)));
+    assert_eq!(blurred_color.rect, blurred_alpha.rect);
+
+    let alpha_rows = surface_rows(&blurred_alpha.surface);
+    let color_rows = surface_rows(&blurred_color.surface);
+    let at = |x: uint, y: uint| alpha_rows[y * 22 + x];
+    assert_eq!(at(0, 0), 0);
+    // The box blur's fixed-point division can round full coverage down slightly.
+    assert!(at(11, 11) >= 254);
+    for y in range(0, 22u) {
+        for x in range(0, 22u) {
+            assert_eq!(at(x, y), at(21 - x, y));
+            assert_eq!(at(x, y), at(x, 21 - y));
+            for channel in range(0, 4u) {
+                assert_eq!(color_rows[(y * 22 + x) * 4 + channel], at(x, y));
+            }
+        }
+    }
+
+    // Nothing needs blurring for a dirty rect far away from the surface.
+    let dirty_rect = Rect(Point2D(100.0, 100.0), Size2D(4.0, 4.0));
+    assert!(box_blur(&alpha, spread_radius, blur_radius, Some(dirty_rect), None).is_none());
+}
+
+#[test]
+fn scaling_averages_a_checkerboard_to_grey() {
+    use azure_hl::{DataSourceSurface, SurfaceFormat, A8, B8G8R8A8, BoxResize, Lanczos3Resize};
+    use azure_hl::{SourceSurfaceMethods, half_scale_surface, scale_surface};
+    use geom::size::Size2D;
+    use std::cmp;
+
+    // A one-pixel opaque black and white checkerboard, which aliases badly when point sampled.
+    let checkerboard = |format: SurfaceFormat| {
+        let bytes_per_pixel = format.bytes_per_pixel();
+        let mut bytes = Vec::new();
+        for y in range(0, 64u) {
+            for x in range(0, 64u) {
+                let grey = if (x + y) % 2 == 0 { 255 } else { 0 };
+                for byte in range(0, bytes_per_pixel) {
+                    let opaque = bytes_per_pixel == 4 && byte == 3;
+                    bytes.push(if opaque { 255 } else { grey });
+                }
+            }
+        }
+        surface_from_rows(Size2D(64, 64), format, bytes.as_slice())
+    };
+    let assert_grey = |surface: &DataSourceSurface, tolerance: u8| {
+        let bytes_per_pixel = surface.format().bytes_per_pixel();
+        for pixel in surface_rows(surface).as_slice().chunks(bytes_per_pixel) {
+            for &value in pixel.slice_to(cmp::min(bytes_per_pixel, 3)).iter() {
+                assert!(value >= 127 - tolerance && value <= 128 + tolerance);
+            }
+            if bytes_per_pixel == 4 {
+                assert_eq!(pixel[3], 255);
+            }
+        }
+    };
+
+    let color = checkerboard(B8G8R8A8);
+    let boxed = scale_surface(&color, Size2D(8, 8), BoxResize);
+    assert_eq!(boxed.size(), Size2D(8, 8));
+    assert_eq!(boxed.format() as uint, B8G8R8A8 as uint);
+    assert_grey(&boxed, 1);
+    // Lanczos rings a little at the edges of the image.
+    assert_grey(&scale_surface(&color, Size2D(16, 16), Lanczos3Resize), 8);
+
+    let alpha = checkerboard(A8);
+    let scaled_alpha = scale_surface(&alpha, Size2D(8, 8), BoxResize);
+    assert_eq!(scaled_alpha.format() as uint, A8 as uint);
+    assert_grey(&scaled_alpha, 1);
+
+    // 64 halves to 32 and then 16, which can't be halved again without going below 10.
+    let halved = half_scale_surface(&color, Size2D(10, 10));
+    assert_eq!(halved.size(), Size2D(16, 16));
+    assert_grey(&halved, 1);
+    assert_eq!(half_scale_surface(&color, Size2D(40, 40)).size(), Size2D(64, 64));
+}
+
+#[test]
+fn scalar_paths_match_sse2_paths() {
+    use azure_hl::{SurfaceFormat, A8, B8G8R8A8};
+    use azure_hl::{box_blur, cpu_features, force_scalar_paths, half_scale_surface};
+    use geom::size::Size2D;
+
+    let features = cpu_features();
+    assert_eq!(features.sse2_enabled, features.sse2_supported && features.sse2_compiled);
+
+    // Noise, so that any difference between the paths shows up somewhere.
+    let noise = |format: SurfaceFormat| {
+        let mut bytes = Vec::new();
+        for y in range(0, 64u) {
+            for x in range(0, 64 * format.bytes_per_pixel()) {
+                bytes.push(((x * 7 + y * 13) * (x + y) % 251) as u8);
+            }
+        }
+        surface_from_rows(Size2D(64, 64), format, bytes.as_slice())
+    };
+    let alpha = noise(A8);
+    let color = noise(B8G8R8A8);
+    let run = || {
+        let blurred = box_blur(&alpha, Size2D(0, 0), Size2D(5, 3), None, None).unwrap();
+        (surface_rows(&blurred.surface),
+         surface_rows(&half_scale_surface(&color, Size2D(10, 10))))
+    };
+
+    // The setting is global, so it's put back even if an assertion fails with it forced.
+    struct ScalarPathsForced;
+    impl Drop for ScalarPathsForced {
+        fn drop(&mut self) {
+            force_scalar_paths(false);
+        }
+    }
+
+    let default_results = run();
+    let scalar_results = {
+        force_scalar_paths(true);
+        let _forced = ScalarPathsForced;
+        assert!(!cpu_features().sse2_enabled);
+        run()
+    };
+    assert_eq!(cpu_features(), features);
+    assert!(default_results == scalar_results);
+}
+
+#[test]
+fn png_round_trip_keeps_every_format() {
+    use azure_hl::{DataSourceSurface, DrawTarget, SkiaBackend, SurfaceFormat, SourceSurfaceMethods};
+    use azure_hl::{B8G8R8A8, B8G8R8X8, R5G6B5, A8};
+    use geom::size::Size2D;
+    use std::io::{MemReader, MemWriter};
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(1, 1), B8G8R8A8);
+    let round_trip = |format: SurfaceFormat, pixels: &[u8]| {
+        let width = (pixels.len() / format.bytes_per_pixel()) as i32;
+        let surface = surface_from_rows(Size2D(width, 1), format, pixels);
+
+        let mut writer = MemWriter::new();
+        surface.write_png(&mut writer).unwrap();
+        let decoded = DataSourceSurface::from_png(&mut MemReader::new(writer.unwrap()),
+                                                  &draw_target).unwrap();
+        assert_eq!(decoded.size(), Size2D(width, 1));
+        (decoded.format() as uint, surface_rows(&decoded))
+    };
+
+    // Premultiplied BGRA survives un-premultiplying and premultiplying again.
+    let bgra = [10, 20, 30, 255, 32, 64, 16, 128, 1, 0, 2, 3, 0, 0, 0, 0];
+    assert_eq!(round_trip(B8G8R8A8, &bgra), (B8G8R8A8 as uint, bgra.to_vec()));
+
+    let (format, pixels) = round_trip(B8G8R8X8, &[10, 20, 30, 0, 200, 100, 50, 0]);
+    assert_eq!(format, B8G8R8X8 as uint);
+    assert_eq!(pixels, vec!(10, 20, 30, 255, 200, 100, 50, 255));
+
+    // Pure red, green and blue, then the 5- and 6-bit fields expand to the full 8 bits.
+    let (format, pixels) = round_trip(R5G6B5, &[0x00, 0xf8, 0xe0, 0x07, 0x1f, 0x00, 0x10, 0x84]);
+    assert_eq!(format, B8G8R8X8 as uint);
+    assert_eq!(pixels, vec!(0, 0, 255, 255, 0, 255, 0, 255, 255, 0, 0, 255, 132, 130, 132, 255));
+
+    let (format, pixels) = round_trip(A8, &[0, 128, 255]);
+    assert_eq!(format, B8G8R8A8 as uint);
+    assert_eq!(pixels, vec!(0, 0, 0, 0, 0, 0, 0, 128, 0, 0, 0, 255));
+}
+
+#[test]
+fn conversions_between_formats_and_pixel_layouts() {
+    use azure_hl::{DataSourceSurface, B8G8R8A8, B8G8R8X8, R5G6B5, A8, SourceSurfaceMethods};
+    use azure_hl::{RGBAOrder, BGRAOrder, PremultipliedAlpha, StraightAlpha};
+    use geom::size::Size2D;
+
+    // Straight-alpha RGBA, as an image decoder would produce it.
+    let straight = [255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0, 128, 128, 128, 255];
+    let surface = DataSourceSurface::from_pixels(Size2D(2, 2), &straight, RGBAOrder, StraightAlpha);
+    assert_eq!(surface.format() as uint, B8G8R8A8 as uint);
+    assert_eq!(surface.to_pixels(BGRAOrder, PremultipliedAlpha),
+               vec!(0, 0, 255, 255, 0, 128, 0, 128, 0, 0, 0, 0, 128, 128, 128, 255));
+    assert_eq!(surface.to_pixels(RGBAOrder, StraightAlpha),
+               vec!(255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 0, 0, 128, 128, 128, 255));
+
+    // Dropping alpha leaves the colors as they look over black.
+    let opaque = surface.convert(B8G8R8X8);
+    assert_eq!(opaque.format() as uint, B8G8R8X8 as uint);
+    assert_eq!(opaque.to_pixels(RGBAOrder, PremultipliedAlpha),
+               vec!(255, 0, 0, 255, 0, 128, 0, 255, 0, 0, 0, 255, 128, 128, 128, 255));
+
+    // 565 keeps the top 5 or 6 bits of each channel and expands them back when read.
+    assert_eq!(opaque.convert(R5G6B5).to_pixels(RGBAOrder, PremultipliedAlpha),
+               vec!(255, 0, 0, 255, 0, 130, 0, 255, 0, 0, 0, 255, 132, 130, 132, 255));
+
+    let alpha = surface.convert(A8);
+    assert_eq!(alpha.format() as uint, A8 as uint);
+    assert_eq!(alpha.convert(B8G8R8A8).to_pixels(BGRAOrder, PremultipliedAlpha),
+               vec!(0, 0, 0, 255, 0, 0, 0, 128, 0, 0, 0, 0, 0, 0, 0, 255));
+}
+
+#[test]
+fn rgba_and_wide_formats_round_trip() {
+    use azure::AzSurfaceFormat;
+    use azure_hl::{DataSourceSurface, DrawTarget, SkiaBackend, SurfaceFormat, SourceSurfaceMethods};
+    use azure_hl::{B8G8R8A8, B8G8R8X8, R8G8B8A8, R8G8B8X8, R16G16B16A16, BGRAOrder};
+    use azure_hl::PremultipliedAlpha;
+    use geom::size::Size2D;
+    use std::mem;
+
+    for &format in [R8G8B8A8, R8G8B8X8, R16G16B16A16].iter() {
+        let value = format as AzSurfaceFormat;
+        assert_eq!(SurfaceFormat::new(value) as AzSurfaceFormat, value);
+    }
+
+    // Premultiplied red, half-transparent green and transparent black, as BGRA.
+    let bgra = [0, 0, 255, 255, 0, 128, 0, 128, 0, 0, 0, 0];
+    let surface = DataSourceSurface::from_pixels(Size2D(3, 1), &bgra, BGRAOrder, PremultipliedAlpha);
+
+    let rgba = surface.convert(R8G8B8A8);
+    assert_eq!(surface_rows(&rgba), vec!(255, 0, 0, 255, 0, 128, 0, 128, 0, 0, 0, 0));
+    assert_eq!(rgba.convert(B8G8R8A8).to_pixels(BGRAOrder, PremultipliedAlpha), bgra.to_vec());
+
+    let rgbx = surface.convert(R8G8B8X8);
+    assert_eq!(surface_rows(&rgbx), vec!(255, 0, 0, 255, 0, 128, 0, 255, 0, 0, 0, 255));
+
+    let wide = surface.convert(R16G16B16A16);
+    let wide_row = surface_rows(&wide);
+    let channels: Vec<u16> = wide_row.as_slice().chunks(2).map(|bytes| {
+        unsafe { mem::transmute::<[u8, ..2], u16>([bytes[0], bytes[1]]) }
+    }).collect();
+    assert_eq!(channels, vec!(65535, 0, 0, 65535, 0, 32896, 0, 32896, 0, 0, 0, 0));
+    assert_eq!(wide.convert(B8G8R8A8).to_pixels(BGRAOrder, PremultipliedAlpha), bgra.to_vec());
+
+    // Draw targets convert these formats to BGRA when they're handed the data.
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(1, 1), B8G8R8A8);
+    for &(source, converted) in [(&rgba, B8G8R8A8), (&rgbx, B8G8R8X8), (&wide, B8G8R8A8)].iter() {
+        let mut data = Vec::new();
+        source.with_data(|bytes| data.push_all(bytes));
+        let uploaded = draw_target.create_source_surface_from_data(data.as_slice(),
+                                                                   source.size(),
+                                                                   source.stride(),
+                                                                   source.format());
+        let uploaded = uploaded.get_data_surface();
+        assert_eq!(uploaded.format() as uint, converted as uint);
+        assert_eq!(uploaded.to_pixels(BGRAOrder, PremultipliedAlpha),
+                   source.to_pixels(BGRAOrder, PremultipliedAlpha));
+    }
+}
+
+#[test]
+fn read_pixels_copies_only_the_requested_rect() {
+    use azure_hl::{Color, ColorPattern, ColorPatternRef, DrawTarget, SkiaBackend};
+    use azure_hl::{B8G8R8A8, R8G8B8A8, BGRAOrder, PremultipliedAlpha, SourceSurfaceMethods};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(4, 4), B8G8R8A8);
+    assert_eq!(draw_target.get_format() as uint, B8G8R8A8 as uint);
+    let red = ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0));
+    draw_target.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(4.0, 4.0)), ColorPatternRef(&red), None);
+    let blue = ColorPattern::new(Color::new(0.0, 0.0, 1.0, 1.0));
+    draw_target.fill_rect(&Rect(Point2D(2.0, 1.0), Size2D(1.0, 2.0)), ColorPatternRef(&blue), None);
+
+    // Two pixels of each of two rows, into a buffer with a padded stride.
+    let rect = Rect(Point2D(1, 1), Size2D(2, 2));
+    let mut bgra = [7u8, ..20];
+    draw_target.read_pixels(rect, B8G8R8A8, &mut bgra, 10);
+    assert_eq!(bgra.to_vec(), vec!(0, 0, 255, 255, 255, 0, 0, 255, 7, 7,
+                                   0, 0, 255, 255, 255, 0, 0, 255, 7, 7));
+
+    let mut rgba = [0u8, ..16];
+    draw_target.read_pixels(rect, R8G8B8A8, &mut rgba, 8);
+    assert_eq!(rgba.to_vec(), vec!(255, 0, 0, 255, 0, 0, 255, 255,
+                                   255, 0, 0, 255, 0, 0, 255, 255));
+
+    // Empty rects read nothing, even when they'd need converting.
+    draw_target.read_pixels(Rect(Point2D(1, 1), Size2D(0, 2)), R8G8B8A8, &mut rgba, 0);
+
+    let snapshot = draw_target.snapshot_rect(Rect(Point2D(2, 0), Size2D(2, 2)));
+    assert_eq!(snapshot.size(), Size2D(2, 2));
+    assert_eq!(snapshot.to_pixels(BGRAOrder, PremultipliedAlpha),
+               vec!(0, 0, 255, 255, 0, 0, 255, 255, 255, 0, 0, 255, 0, 0, 255, 255));
+}
+
+#[test]
+fn transforms_round_trip_through_the_draw_target() {
+    use azure_hl::{B8G8R8A8, DrawTarget, Matrix, SkiaBackend};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+    use std::f32::consts::FRAC_PI_2;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(4, 4), B8G8R8A8);
+    assert!(Matrix::from_matrix2d(&draw_target.get_transform()).is_identity());
+
+    let scale = Matrix::scale(2.0, 4.0);
+    draw_target.set_transform(&scale.to_matrix2d());
+    draw_target.concat_transform(&Matrix::translation(1.0, 1.0).to_matrix2d());
+    // The translation acts in the scaled space.
+    let transform = Matrix::from_matrix2d(&draw_target.get_transform());
+    assert_eq!(transform, Matrix::new(2.0, 0.0, 0.0, 4.0, 2.0, 4.0));
+    assert_eq!(transform, Matrix::translation(1.0, 1.0).multiply(&scale));
+    assert_eq!(transform.transform_point(&Point2D(1.0, 1.0)), Point2D(4.0, 8.0));
+
+    let inverse = transform.inverse().unwrap();
+    assert!(transform.multiply(&inverse).is_identity());
+    assert_eq!(Matrix::scale(0.0, 1.0).inverse(), None);
+
+    let rotation = Matrix::rotation(FRAC_PI_2);
+    assert!(rotation.is_rectilinear());
+    assert!(!Matrix::rotation(FRAC_PI_2 / 2.0).is_rectilinear());
+    let bounds = rotation.transform_bounds(&Rect(Point2D(0.0, 0.0), Size2D(2.0, 1.0)));
+    assert!((bounds.origin.x + 1.0).abs() < 1e-6 && bounds.origin.y.abs() < 1e-6);
+    assert!((bounds.size.width - 1.0).abs() < 1e-6 && (bounds.size.height - 2.0).abs() < 1e-6);
+}
+
+#[test]
+fn restore_pops_the_clips_and_state_of_the_saved_scope() {
+    use azure_hl::{B8G8R8A8, BGRAOrder, Color, ColorPattern, ColorPatternRef, DrawTarget};
+    use azure_hl::{OverOp, PremultipliedAlpha, SkiaBackend, SourceOp};
+    use geom::matrix2d::Matrix2D;
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(2, 1), B8G8R8A8);
+    {
+        let _guard = draw_target.scoped_save();
+        draw_target.set_transform(&Matrix2D::new(1.0, 0.0, 0.0, 1.0, 1.0, 0.0));
+        draw_target.set_global_alpha(0.5);
+        draw_target.set_composition_op(SourceOp);
+        draw_target.push_clip_rect(&Rect(Point2D(-1.0, 0.0), Size2D(1.0, 1.0)));
+        // Left unbalanced; the guard restores it too.
+        draw_target.save();
+        draw_target.push_clip_rect(&Rect(Point2D(-1.0, 0.0), Size2D(2.0, 1.0)));
+        assert_eq!(draw_target.save_depth(), 2);
+    }
+    assert_eq!(draw_target.save_depth(), 0);
+    assert_eq!(draw_target.global_alpha(), 1.0);
+    assert_eq!(draw_target.composition_op() as uint, OverOp as uint);
+    assert_eq!(draw_target.get_transform(), Matrix2D::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+
+    // Neither clip survives, so this covers both pixels.
+    let red = ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0));
+    draw_target.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(2.0, 1.0)),
+                          ColorPatternRef(&red),
+                          Some(&draw_target.draw_options()));
+    let pixels = draw_target.snapshot().get_data_surface().to_pixels(BGRAOrder, PremultipliedAlpha);
+    assert_eq!(pixels, vec!(0, 0, 255, 255, 0, 0, 255, 255));
+
+    // A guard doesn't panic when a clip pushed before it was popped inside its scope.
+    draw_target.push_clip_rect(&Rect(Point2D(0.0, 0.0), Size2D(1.0, 1.0)));
+    {
+        let _guard = draw_target.scoped_save();
+        draw_target.pop_clip();
+    }
+    assert_eq!(draw_target.save_depth(), 0);
+    assert_eq!(draw_target.clip_depth(), 0);
+}
+
+#[test]
+fn fill_rect_without_options_uses_the_global_state() {
+    use azure_hl::{B8G8R8A8, BGRAOrder, Color, ColorPattern, ColorPatternRef, DrawTarget};
+    use azure_hl::{PremultipliedAlpha, SkiaBackend};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(1, 1), B8G8R8A8);
+    draw_target.set_global_alpha(0.5);
+    let red = ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0));
+    draw_target.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(1.0, 1.0)), ColorPatternRef(&red), None);
+    let pixels = draw_target.snapshot().get_data_surface().to_pixels(BGRAOrder, PremultipliedAlpha);
+    assert!(pixels[2] >= 127 && pixels[2] <= 128);
+    assert_eq!(pixels[2], pixels[3]);
+}
+
+#[test]
+fn fill_without_options_uses_the_global_state() {
+    use azure_hl::{B8G8R8A8, BGRAOrder, Color, ColorPattern, ColorPatternRef, DrawTarget};
+    use azure_hl::{PremultipliedAlpha, SkiaBackend, SourceOp};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(1, 1), B8G8R8A8);
+    let blue = ColorPattern::new(Color::new(0.0, 0.0, 1.0, 1.0));
+    draw_target.fill_rect(&Rect(Point2D(0.0, 0.0), Size2D(1.0, 1.0)), ColorPatternRef(&blue), None);
+
+    // The source op replaces the blue rather than blending over it.
+    draw_target.set_global_alpha(0.5);
+    draw_target.set_composition_op(SourceOp);
+    let path_builder = draw_target.create_path_builder();
+    path_builder.move_to(Point2D(0.0, 0.0));
+    path_builder.line_to(Point2D(1.0, 0.0));
+    path_builder.line_to(Point2D(1.0, 1.0));
+    path_builder.line_to(Point2D(0.0, 1.0));
+    let red = ColorPattern::new(Color::new(1.0, 0.0, 0.0, 1.0));
+    draw_target.fill(&path_builder.finish(), &red, None);
+
+    let pixels = draw_target.snapshot().get_data_surface().to_pixels(BGRAOrder, PremultipliedAlpha);
+    assert_eq!(pixels[0], 0);
+    assert!(pixels[2] >= 127 && pixels[2] <= 128);
+    assert_eq!(pixels[2], pixels[3]);
+}
+
+#[test]
+fn clip_guards_track_the_clip_stack() {
+    use azure_hl::{B8G8R8A8, DrawTarget, SkiaBackend};
+    use geom::matrix2d::Matrix2D;
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(8, 8), B8G8R8A8);
+    assert_eq!(draw_target.clip_depth(), 0);
+    assert_eq!(draw_target.clip_bounds(), Rect(Point2D(0.0, 0.0), Size2D(8.0, 8.0)));
+
+    draw_target.set_transform(&Matrix2D::new(2.0, 0.0, 0.0, 2.0, 0.0, 0.0));
+    {
+        let _outer = draw_target.push_clip_rect_scoped(&Rect(Point2D(1.0, 1.0), Size2D(2.0, 2.0)));
+        let path_builder = draw_target.create_path_builder();
+        path_builder.move_to(Point2D(0.0, 0.0));
+        path_builder.line_to(Point2D(2.0, 0.0));
+        path_builder.line_to(Point2D(2.0, 2.0));
+        path_builder.line_to(Point2D(0.0, 2.0));
+        let path = path_builder.finish();
+        draw_target.with_clip(&path, |draw_target| {
+            assert_eq!(draw_target.clip_depth(), 2);
+            // Clipped to device space (2, 2)-(4, 4), which is (1, 1)-(2, 2) in user space.
+            assert_eq!(draw_target.clip_bounds(), Rect(Point2D(1.0, 1.0), Size2D(1.0, 1.0)));
+        });
+        assert_eq!(draw_target.clip_depth(), 1);
+        // Leaked inside the guard's scope, and popped along with its clip.
+        draw_target.push_clip_rect(&Rect(Point2D(0.0, 0.0), Size2D(1.0, 1.0)));
+    }
+    assert_eq!(draw_target.clip_depth(), 0);
+}
+
+#[test]
+fn clip_guards_only_pop_their_own_clips() {
+    use azure_hl::{B8G8R8A8, DrawTarget, SkiaBackend};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(4, 4), B8G8R8A8);
+    let rect = Rect(Point2D(0.0, 0.0), Size2D(2.0, 2.0));
+    draw_target.save();
+    {
+        let _guard = draw_target.push_clip_rect_scoped(&rect);
+        // Pops the guard's clip, so the clip pushed next isn't the guard's to pop.
+        draw_target.restore();
+        draw_target.push_clip_rect(&rect);
+    }
+    assert_eq!(draw_target.clip_depth(), 1);
+    draw_target.pop_clip();
+}
+
+#[test]
+#[should_fail]
+fn popping_a_clip_that_was_never_pushed_panics() {
+    use azure_hl::{B8G8R8A8, DrawTarget, SkiaBackend};
+    use geom::size::Size2D;
+
+    DrawTarget::new(SkiaBackend, Size2D(1, 1), B8G8R8A8).pop_clip();
+}
+
+#[test]
+#[should_fail]
+fn snapshotting_outside_the_draw_target_panics() {
+    use azure_hl::{B8G8R8A8, DrawTarget, SkiaBackend};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(4, 4), B8G8R8A8);
+    draw_target.snapshot_rect(Rect(Point2D(2, 2), Size2D(4, 4)));
+}
+
+#[test]
+#[should_fail]
+fn reading_pixels_with_a_negative_stride_panics() {
+    use azure_hl::{B8G8R8A8, DrawTarget, SkiaBackend};
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(4, 4), B8G8R8A8);
+    let mut data = [0u8, ..8];
+    draw_target.read_pixels(Rect(Point2D(0, 0), Size2D(1, 2)), B8G8R8A8, data.as_mut_slice(), -4);
+}
+
+/// Loads `tests/fonts/OutlineGlyph.ttf`, whose glyph 1 is a square 3/4 em wide with its bottom
+/// left corner at the origin.
+#[cfg(target_os = "linux")]
+fn outline_glyph_font(size: f32) -> ::scaled_font::ScaledFont {
+    use scaled_font::ScaledFont;
+    use std::io::File;
+    use sync::Arc;
+
+    let data = File::open(&Path::new("tests/fonts/OutlineGlyph.ttf")).read_to_end().unwrap();
+    ScaledFont::from_truetype_data(Arc::new(data), 0, size).unwrap()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn glyph_outlines_scale_with_the_font_size() {
+    use azure_hl::{B8G8R8A8, DrawTarget, Glyph, SkiaBackend};
+    use geom::matrix2d::Matrix2D;
+    use geom::point::Point2D;
+    use geom::rect::Rect;
+    use geom::size::Size2D;
+
+    let draw_target = DrawTarget::new(SkiaBackend, Size2D(1, 1), B8G8R8A8);
+    let glyphs = [Glyph::new(1, Point2D(8.0, 40.0))];
+    let identity = Matrix2D::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+    let bounds = |size: f32| {
+        let path = outline_glyph_font(size).path_for_glyphs(&glyphs, &draw_target).unwrap();
+        path.get_bounds(&identity)
+    };
+    // The square rises from the glyph's origin.
+    assert_eq!(bounds(32.0), Rect(Point2D(8.0, 16.0), Size2D(24.0, 24.0)));
+    assert_eq!(bounds(16.0), Rect(Point2D(8.0, 28.0), Size2D(12.0, 12.0)));
+
+    // Builders get the same outline.
+    let builder = draw_target.create_path_builder();
+    assert!(outline_glyph_font(32.0).copy_glyphs_to_builder(&glyphs, &builder));
+    assert_eq!(builder.finish().get_bounds(&identity), bounds(32.0));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn truetype_data_is_kept_alive_and_checked() {
+    use scaled_font::ScaledFont;
+    use std::io::File;
+    use sync::Arc;
+
+    let font = {
+        let data = File::open(&Path::new("tests/fonts/ColorGlyph.ttf")).read_to_end().unwrap();
+        ScaledFont::from_truetype_data(Arc::new(data), 0, 32.0).unwrap()
+    };
+    // The font outlives the caller's reference to the data it was made from.
+    assert_eq!(font.glyph_index('A'), Some(1));
+
+    let not_a_font = Arc::new(b"not a font".to_vec());
+    assert!(ScaledFont::from_truetype_data(not_a_font, 0, 32.0).is_none());
+    let data = File::open(&Path::new("tests/fonts/ColorGlyph.ttf")).read_to_end().unwrap();
+    assert!(ScaledFont::from_truetype_data(Arc::new(data), 1, 32.0).is_none());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn font_metrics_come_from_the_font_tables() {
+    use scaled_font::ScaledFont;
+    use std::io::File;
+    use sync::Arc;
+
+    // ColorGlyph.ttf has a 32 pixel em at this size, an ascent of a whole em and an underline 2
+    // pixels below the baseline, 1 pixel thick. Glyph 1, for 'A', advances by an em.
+    let data = File::open(&Path::new("tests/fonts/ColorGlyph.ttf")).read_to_end().unwrap();
+    let font = ScaledFont::from_truetype_data(Arc::new(data), 0, 32.0).unwrap();
+    let metrics = font.metrics();
+    assert!(metrics.ascent > 0.0);
+    assert_eq!(metrics.underline_position, 2.0);
+    assert_eq!(metrics.underline_thickness, 1.0);
+    assert_eq!(font.glyph_advance(1), 32.0);
+    assert_eq!(font.glyph_index('A'), Some(1));
+    assert_eq!(font.glyph_index('Z'), None);
+}
@@ -0,0 +1,183 @@
+# Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+
+# Writes ColorGlyph.ttf, a minimal color bitmap font used by the reftests.
+#
+# The font has a single CBDT strike at 32 pixels per em, and maps 'A' to glyph 1: a 32x32
+# image with a 4 pixel transparent border around a square whose top half is red and whose
+# bottom half is blue. The image sits on the baseline and advances by its width. Its 'post'
+# table puts the underline 2 pixels below the baseline, 1 pixel thick.
+#
+# Usage: python make_color_font.py ColorGlyph.ttf
+
+import struct
+import sys
+import zlib
+
+PPEM = 32
+BORDER = 4
+UNITS_PER_EM = 2048
+
+
+def png(width, height, pixel):
+    """Encodes an RGBA8 PNG, calling pixel(x, y) for each pixel's (r, g, b, a)."""
+    rows = b""
+    for y in range(height):
+        rows += b"\0" + b"".join(struct.pack("4B", *pixel(x, y)) for x in range(width))
+
+    def chunk(kind, data):
+        return (struct.pack(">I", len(data)) + kind + data +
+                struct.pack(">I", zlib.crc32(kind + data) & 0xffffffff))
+
+    return (b"\x89PNG\r\n\x1a\n" +
+            chunk(b"IHDR", struct.pack(">IIBBBBB", width, height, 8, 6, 0, 0, 0)) +
+            chunk(b"IDAT", zlib.compress(rows)) +
+            chunk(b"IEND", b""))
+
+
+def glyph_pixel(x, y):
+    if x < BORDER or y < BORDER or x >= PPEM - BORDER or y >= PPEM - BORDER:
+        return (0, 0, 0, 0)
+    if y < PPEM // 2:
+        return (255, 0, 0, 255)
+    return (0, 0, 255, 255)
+
+
+def head():
+    return struct.pack(">IIIIHHqqhhhhHHhhh",
+                       0x00010000, 0x00010000, 0, 0x5F0F3CF5, 0x000B, UNITS_PER_EM,
+                       0, 0, 0, 0, UNITS_PER_EM, UNITS_PER_EM, 0, 8, 2, 0, 0)
+
+
+def hhea():
+    return struct.pack(">IhhhHhhhhhhhhhhhH",
+                       0x00010000, UNITS_PER_EM, 0, 0, UNITS_PER_EM, 0, 0, UNITS_PER_EM,
+                       1, 0, 0, 0, 0, 0, 0, 0, 2)
+
+
+def hmtx():
+    return struct.pack(">HhHh", UNITS_PER_EM, 0, UNITS_PER_EM, 0)
+
+
+def maxp():
+    return struct.pack(">IH", 0x00005000, 2)
+
+
+def cmap():
+    # A format 4 subtable with a segment mapping 'A' to glyph 1 and the final 0xFFFF segment.
+    segments = [(0x41, 0x41, 1 - 0x41), (0xFFFF, 0xFFFF, 1)]
+    count = len(segments)
+    # With two segments: searchRange 4, entrySelector 1 and rangeShift 0.
+    subtable = struct.pack(">HHHH", count * 2, 4, 1, 0)
+    subtable += b"".join(struct.pack(">H", end) for _, end, _ in segments)
+    subtable += struct.pack(">H", 0)
+    subtable += b"".join(struct.pack(">H", start) for start, _, _ in segments)
+    subtable += b"".join(struct.pack(">h", delta) for _, _, delta in segments)
+    subtable += b"".join(struct.pack(">H", 0) for _ in segments)
+    subtable = struct.pack(">HHH", 4, 6 + len(subtable), 0) + subtable
+    return struct.pack(">HHHHI", 0, 1, 3, 1, 12) + subtable
+
+
+def name(family):
+    records = [(1, family), (2, "Regular"), (4, family), (6, family.replace(" ", ""))]
+    strings = b""
+    table = b""
+    for name_id, value in records:
+        encoded = value.encode("utf-16-be")
+        table += struct.pack(">HHHHHH", 3, 1, 0x409, name_id, len(encoded), len(strings))
+        strings += encoded
+    return struct.pack(">HHH", 0, len(records), 6 + 12 * len(records)) + table + strings
+
+
+def post():
+    # The underline is 1/16 em below the baseline and 1/32 em thick: 2 and 1 pixels at 32 ppem.
+    return struct.pack(">IIhhIIIII", 0x00030000, 0, -UNITS_PER_EM // 16, UNITS_PER_EM // 32,
+                       0, 0, 0, 0, 0)
+
+
+def cbdt_and_cblc():
+    image = png(PPEM, PPEM, glyph_pixel)
+    # Glyph data format 17: small metrics, then the PNG.
+    glyph = struct.pack(">BBbbB", PPEM, PPEM, 0, PPEM, PPEM) + struct.pack(">I", len(image)) + image
+    cbdt = struct.pack(">HH", 3, 0) + glyph
+    image_data_offset = 4
+
+    # One index subtable of format 1 covering glyph 1, in image format 17.
+    subtable = struct.pack(">HHIII", 1, 17, image_data_offset, 0, len(glyph))
+    subtable_array = struct.pack(">HHI", 1, 1, 8)
+    index_tables = subtable_array + subtable
+
+    line_metrics = struct.pack(">bbBbbbbbbbbb", PPEM, 0, PPEM, 1, 0, 0, 0, 0, 0, 0, 0, 0)
+    header_size = 8
+    bitmap_size_size = 48
+    bitmap_size = (struct.pack(">IIII", header_size + bitmap_size_size, len(index_tables), 1, 0) +
+                   line_metrics + line_metrics +
+                   struct.pack(">HHBBBb", 1, 1, PPEM, PPEM, 32, 1))
+    cblc = struct.pack(">HHI", 3, 0, 1) + bitmap_size + index_tables
+    return cbdt, cblc
+
+
+def checksum(data):
+    data += b"\0" * (-len(data) % 4)
+    return sum(struct.unpack(">%dI" % (len(data) // 4), data)) & 0xffffffff
+
+
+def font():
+    cbdt, cblc = cbdt_and_cblc()
+    return sfnt({
+        b"CBDT": cbdt,
+        b"CBLC": cblc,
+        b"cmap": cmap(),
+        b"head": head(),
+        b"hhea": hhea(),
+        b"hmtx": hmtx(),
+        b"maxp": maxp(),
+        b"name": name("Azure Color Glyph"),
+        b"post": post(),
+    })
+
+
+def sfnt(tables):
+    """Lays out a font file holding the given tables, keyed by tag."""
+    tables = sorted(tables.items())
+
+    count = len(tables)
+    search_range = 1
+    entry_selector = 0
+    while search_range * 2 <= count:
+        search_range *= 2
+        entry_selector += 1
+    directory = struct.pack(">IHHHH", 0x00010000, count, search_range * 16, entry_selector,
+                            count * 16 - search_range * 16)
+
+    offset = len(directory) + 16 * count
+    records = b""
+    data = b""
+    for tag, table in tables:
+        records += struct.pack(">4sIII", tag, checksum(table), offset + len(data), len(table))
+        data += table + b"\0" * (-len(table) % 4)
+    result = bytearray(directory + records + data)
+
+    # Patch head.checkSumAdjustment now that the whole file is known.
+    head_offset = struct.unpack(">I", records[16 * [t for t, _ in tables].index(b"head") + 8:][:4])[0]
+    adjustment = (0xB1B0AFBA - checksum(bytes(result))) & 0xffffffff
+    result[head_offset + 8:head_offset + 12] = struct.pack(">I", adjustment)
+    return bytes(result)
+
+
+def main():
+    if len(sys.argv) != 2:
+        sys.stderr.write("usage: %s <output.ttf>\n" % sys.argv[0])
+        sys.exit(2)
+    with open(sys.argv[1], "wb") as output:
+        output.write(font())
+
+
+if __name__ == "__main__":
+    main()
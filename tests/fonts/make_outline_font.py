@@ -0,0 +1,76 @@
+# Copyright 2013 The Servo Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+
+# Writes OutlineGlyph.ttf, a minimal TrueType font used by the glyph outline tests.
+#
+# The font maps 'A' to glyph 1, a square outline 3/4 em wide whose bottom left corner is at the
+# origin. It shares its metrics with ColorGlyph.ttf.
+#
+# Usage: python make_outline_font.py OutlineGlyph.ttf
+
+import struct
+import sys
+
+from make_color_font import UNITS_PER_EM, cmap, head, hhea, hmtx, name, post, sfnt
+
+SIDE = UNITS_PER_EM * 3 // 4
+
+
+def square_glyph():
+    points = [(0, 0), (0, SIDE), (SIDE, SIDE), (SIDE, 0)]
+    # One contour, with its bounding box.
+    glyph = struct.pack(">hhhhh", 1, 0, 0, SIDE, SIDE)
+    # The index of the contour's last point, no instructions, and every point on the curve.
+    glyph += struct.pack(">HH", len(points) - 1, 0) + b"\x01" * len(points)
+    previous = (0, 0)
+    deltas = []
+    for point in points:
+        deltas.append((point[0] - previous[0], point[1] - previous[1]))
+        previous = point
+    glyph += b"".join(struct.pack(">h", x) for x, _ in deltas)
+    glyph += b"".join(struct.pack(">h", y) for _, y in deltas)
+    return glyph + b"\0" * (len(glyph) % 2)
+
+
+def glyf_and_loca():
+    # Glyph 0 is empty. Short offsets are stored halved.
+    glyph = square_glyph()
+    return glyph, struct.pack(">HHH", 0, 0, len(glyph) // 2)
+
+
+def maxp():
+    # Version 1.0, as TrueType outlines need: 2 glyphs, at most 4 points in 1 contour, 2 zones.
+    return struct.pack(">IHHHHHHHHHHHHHH", 0x00010000, 2, 4, 1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0)
+
+
+def font():
+    glyf, loca = glyf_and_loca()
+    return sfnt({
+        b"cmap": cmap(),
+        b"glyf": glyf,
+        b"head": head(),
+        b"hhea": hhea(),
+        b"hmtx": hmtx(),
+        b"loca": loca,
+        b"maxp": maxp(),
+        b"name": name("Azure Outline Glyph"),
+        b"post": post(),
+    })
+
+
+def main():
+    if len(sys.argv) != 2:
+        sys.stderr.write("usage: %s <output.ttf>\n" % sys.argv[0])
+        sys.exit(2)
+    with open(sys.argv[1], "wb") as output:
+        output.write(font())
+
+
+if __name__ == "__main__":
+    main()
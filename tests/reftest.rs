@@ -0,0 +1,330 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Pixel-comparison tests. Each scene is rendered onto a CPU draw target for every available
+//! backend, read back and compared against a reference PNG in `tests/references`.
+//!
+//! On failure the rendered image and a diff image are written to `target/reftest`. Setting
+//! `AZURE_UPDATE_REFERENCES=1` overwrites the references with the Skia rendering instead.
+
+extern crate azure;
+extern crate geom;
+extern crate png;
+extern crate sync;
+
+use azure::azure_hl::{BackendType, SkiaBackend, Color, ColorPattern, ColorPatternRef};
+use azure::azure_hl::{DrawTarget, B8G8R8A8, SourceSurfaceMethods, DrawOptions, Glyph};
+use azure::azure_hl::{GlyphRenderingOptionsBuilder, CornerRadii};
+use azure::scaled_font::ScaledFont;
+use geom::matrix2d::Matrix2D;
+use geom::point::Point2D;
+use geom::rect::Rect;
+use geom::size::Size2D;
+use std::cmp;
+use std::io;
+use std::io::fs;
+use std::os;
+use sync::Arc;
+
+#[cfg(feature = "cairo")]
+use azure::azure_hl::CairoBackend;
+
+/// The maximum difference allowed between a rendered and a reference channel value.
+static TOLERANCE: u8 = 2;
+
+static SIZE: i32 = 64;
+
+#[cfg(not(feature = "cairo"))]
+fn backends() -> Vec<BackendType> {
+    vec!(SkiaBackend)
+}
+
+#[cfg(feature = "cairo")]
+fn backends() -> Vec<BackendType> {
+    vec!(SkiaBackend, CairoBackend)
+}
+
+/// Reads back the contents of a draw target as non-premultiplied RGBA.
+fn read_back(draw_target: &DrawTarget) -> Vec<u8> {
+    let data_surface = draw_target.snapshot().get_data_surface();
+    let size = data_surface.size();
+    let stride = data_surface.stride() as uint;
+    let mut pixels = Vec::with_capacity((size.width * size.height * 4) as uint);
+    data_surface.with_data(|data| {
+        for y in range(0, size.height as uint) {
+            for x in range(0, size.width as uint) {
+                let offset = y * stride + x * 4;
+                let (b, g, r, a) = (data[offset], data[offset + 1], data[offset + 2], data[offset + 3]);
+                let unpremultiply = |c: u8| {
+                    if a == 0 {
+                        0
+                    } else {
+                        cmp::min((c as f32 * 255.0 / a as f32).round() as uint, 255) as u8
+                    }
+                };
+                pixels.push(unpremultiply(r));
+                pixels.push(unpremultiply(g));
+                pixels.push(unpremultiply(b));
+                pixels.push(a);
+            }
+        }
+    });
+    pixels
+}
+
+fn channel_difference(a: u8, b: u8) -> u8 {
+    if a > b { a - b } else { b - a }
+}
+
+fn write_png(path: &Path, pixels: Vec<u8>) {
+    let mut image = png::Image {
+        width: SIZE as u32,
+        height: SIZE as u32,
+        pixels: png::RGBA8(pixels),
+    };
+    png::store_png(&mut image, path).unwrap();
+}
+
+fn check_scene(name: &str, scene: |&DrawTarget|) {
+    check_scene_with_backends(name, backends(), scene)
+}
+
+fn check_scene_with_backends(name: &str, backends: Vec<BackendType>, scene: |&DrawTarget|) {
+    let reference_path = Path::new("tests/references").join(format!("{}.png", name));
+    let output_dir = Path::new("target/reftest");
+
+    for backend in backends.into_iter() {
+        let draw_target = DrawTarget::new(backend.clone(), Size2D(SIZE, SIZE), B8G8R8A8);
+        scene(&draw_target);
+        draw_target.flush();
+        let rendered = read_back(&draw_target);
+
+        if backend == SkiaBackend && os::getenv("AZURE_UPDATE_REFERENCES").is_some() {
+            write_png(&reference_path, rendered);
+            continue;
+        }
+
+        let reference = match png::load_png(&reference_path) {
+            Ok(png::Image { pixels: png::RGBA8(pixels), .. }) => pixels,
+            Ok(_) => panic!("reference {} is not an RGBA image", reference_path.display()),
+            Err(error) => panic!("couldn't load reference {}: {}", reference_path.display(), error),
+        };
+        assert_eq!(reference.len(), rendered.len());
+
+        let mut mismatches = 0u;
+        let mut diff = Vec::with_capacity(rendered.len());
+        for (expected, actual) in reference.as_slice().chunks(4).zip(rendered.as_slice().chunks(4)) {
+            // Fully transparent pixels match regardless of their color channels.
+            let matches = (expected[3] == 0 && actual[3] == 0) ||
+                range(0, 4u).all(|i| channel_difference(expected[i], actual[i]) <= TOLERANCE);
+            if matches {
+                diff.push_all(&[0, 0, 0, 255]);
+            } else {
+                mismatches += 1;
+                diff.push_all(&[255, 0, 255, 255]);
+            }
+        }
+
+        if mismatches != 0 {
+            fs::mkdir_recursive(&output_dir, io::USER_RWX).unwrap();
+            let prefix = format!("{}-{}", name, backend.as_azure_backend_type());
+            write_png(&output_dir.join(format!("{}.png", prefix)), rendered);
+            write_png(&output_dir.join(format!("{}-diff.png", prefix)), diff);
+            panic!("{}: {} pixels differ from the reference on backend {}; see {}",
+                   name,
+                   mismatches,
+                   backend.as_azure_backend_type(),
+                   output_dir.display());
+        }
+    }
+}
+
+fn fill(draw_target: &DrawTarget, rect: Rect<f32>, color: Color) {
+    let pattern = ColorPattern::new(color);
+    draw_target.fill_rect(&rect, ColorPatternRef(&pattern), None);
+}
+
+fn fill_background(draw_target: &DrawTarget) {
+    fill(draw_target,
+         Rect(Point2D(0.0, 0.0), Size2D(SIZE as f32, SIZE as f32)),
+         Color::new(1.0, 1.0, 1.0, 1.0));
+}
+
+#[test]
+fn solid_fill() {
+    check_scene("solid_fill", |draw_target| {
+        fill_background(draw_target);
+        fill(draw_target,
+             Rect(Point2D(16.0, 16.0), Size2D(32.0, 32.0)),
+             Color::new(1.0, 0.0, 0.0, 1.0));
+    });
+}
+
+#[test]
+fn alpha_over() {
+    check_scene("alpha_over", |draw_target| {
+        fill_background(draw_target);
+        fill(draw_target,
+             Rect(Point2D(0.0, 0.0), Size2D(SIZE as f32, SIZE as f32)),
+             Color::new(0.0, 0.0, 1.0, 0.5));
+    });
+}
+
+#[test]
+fn clip_rect() {
+    check_scene("clip_rect", |draw_target| {
+        fill_background(draw_target);
+        draw_target.push_clip_rect(&Rect(Point2D(0.0, 0.0), Size2D(32.0, SIZE as f32)));
+        fill(draw_target,
+             Rect(Point2D(0.0, 0.0), Size2D(SIZE as f32, SIZE as f32)),
+             Color::new(0.0, 1.0, 0.0, 1.0));
+        draw_target.pop_clip();
+    });
+}
+
+#[test]
+fn transform() {
+    check_scene("transform", |draw_target| {
+        fill_background(draw_target);
+        draw_target.set_transform(&Matrix2D::identity().translate(8.0, 8.0));
+        fill(draw_target,
+             Rect(Point2D(0.0, 0.0), Size2D(16.0, 16.0)),
+             Color::new(0.0, 0.0, 0.0, 1.0));
+    });
+}
+
+#[test]
+fn clear_rect() {
+    check_scene("clear_rect", |draw_target| {
+        fill(draw_target,
+             Rect(Point2D(0.0, 0.0), Size2D(SIZE as f32, SIZE as f32)),
+             Color::new(1.0, 0.0, 0.0, 1.0));
+        draw_target.clear_rect(&Rect(Point2D(16.0, 16.0), Size2D(32.0, 32.0)));
+    });
+}
+
+/// Loads `tests/fonts/ColorGlyph.ttf` at its strike size. Glyph 1 is a 32x32 color bitmap with
+/// a 4 pixel transparent border around a square, red on top and blue below, sitting on the
+/// baseline.
+fn color_glyph_font() -> ScaledFont {
+    let path = Path::new("tests/fonts/ColorGlyph.ttf");
+    let data = io::File::open(&path).read_to_end().unwrap();
+    ScaledFont::from_truetype_data(Arc::new(data), 0, 32.0).unwrap()
+}
+
+fn fill_color_glyph(draw_target: &DrawTarget, color: Color, color_glyphs: bool) {
+    let font = color_glyph_font();
+    let pattern = ColorPattern::new(color);
+    let rendering_options = GlyphRenderingOptionsBuilder::new().color_glyphs(color_glyphs).finish();
+    draw_target.fill_glyphs(&font,
+                            &[Glyph::new(1, Point2D(16.0, 48.0))],
+                            ColorPatternRef(&pattern),
+                            Some(&DrawOptions::new(1.0, 0)),
+                            Some(&rendering_options));
+}
+
+// Fonts loaded from TrueType data are only drawable with Skia.
+#[cfg(target_os = "linux")]
+#[test]
+fn color_glyphs() {
+    check_scene_with_backends("color_glyphs", vec!(SkiaBackend), |draw_target| {
+        fill_background(draw_target);
+        fill_color_glyph(draw_target, Color::new(0.0, 0.0, 0.0, 1.0), true);
+    });
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn color_glyphs_tinted() {
+    check_scene_with_backends("color_glyphs_tinted", vec!(SkiaBackend), |draw_target| {
+        fill_background(draw_target);
+        fill_color_glyph(draw_target, Color::new(0.0, 1.0, 0.0, 1.0), false);
+    });
+}
+
+#[test]
+fn box_shadow_outset() {
+    check_scene("box_shadow_outset", |draw_target| {
+        fill_background(draw_target);
+        let rect = Rect(Point2D(16.0, 16.0), Size2D(24.0, 24.0));
+        draw_target.draw_box_shadow(&rect,
+                                    &CornerRadii::uniform(0.0),
+                                    &Color::new(0.0, 0.0, 0.0, 1.0),
+                                    &Point2D(8.0, 8.0),
+                                    2.0,
+                                    0.0,
+                                    false,
+                                    None,
+                                    None);
+        fill(draw_target, rect, Color::new(1.0, 0.0, 0.0, 1.0));
+    });
+}
+
+#[test]
+fn box_shadow_inset() {
+    check_scene("box_shadow_inset", |draw_target| {
+        fill_background(draw_target);
+        draw_target.draw_box_shadow(&Rect(Point2D(16.0, 16.0), Size2D(32.0, 32.0)),
+                                    &CornerRadii::uniform(0.0),
+                                    &Color::new(0.0, 0.0, 0.0, 1.0),
+                                    &Point2D(4.0, 4.0),
+                                    4.0,
+                                    0.0,
+                                    true,
+                                    None,
+                                    None);
+    });
+}
+
+/// A blurred shadow with rounded corners, big enough that the middle of its mask is
+/// stretched. It is offset clear of its box, so the box's clip doesn't touch it.
+#[test]
+fn box_shadow_blurred() {
+    check_scene("box_shadow_blurred", |draw_target| {
+        fill_background(draw_target);
+        draw_target.draw_box_shadow(&Rect(Point2D(2.0, 2.0), Size2D(24.0, 24.0)),
+                                    &CornerRadii::uniform(4.0),
+                                    &Color::new(0.0, 0.0, 0.0, 1.0),
+                                    &Point2D(30.0, 30.0),
+                                    0.0,
+                                    1.5,
+                                    false,
+                                    None,
+                                    None);
+    });
+}
+
+/// A blurred shadow with rounded corners, centered on its box, comes out the same when
+/// flipped either way.
+#[test]
+fn box_shadow_blur_is_symmetric() {
+    for backend in backends().into_iter() {
+        let draw_target = DrawTarget::new(backend, Size2D(SIZE, SIZE), B8G8R8A8);
+        draw_target.draw_box_shadow(&Rect(Point2D(20.0, 20.0), Size2D(24.0, 24.0)),
+                                    &CornerRadii::uniform(6.0),
+                                    &Color::new(0.0, 0.0, 0.0, 1.0),
+                                    &Point2D(0.0, 0.0),
+                                    2.0,
+                                    3.0,
+                                    false,
+                                    None,
+                                    None);
+        draw_target.flush();
+        let pixels = read_back(&draw_target);
+        let alpha = |x: uint, y: uint| pixels[(y * SIZE as uint + x) * 4 + 3];
+
+        let size = SIZE as uint;
+        for y in range(0, size) {
+            for x in range(0, size) {
+                assert!(channel_difference(alpha(x, y), alpha(size - 1 - x, y)) <= TOLERANCE);
+                assert!(channel_difference(alpha(x, y), alpha(x, size - 1 - y)) <= TOLERANCE);
+            }
+        }
+        // Nothing inside the box, a dark band just outside it and nothing far away.
+        assert_eq!(alpha(32, 32), 0);
+        assert!(alpha(32, 19) > 128);
+        assert_eq!(alpha(32, 1), 0);
+        assert_eq!(alpha(1, 1), 0);
+    }
+}